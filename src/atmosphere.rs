@@ -0,0 +1,242 @@
+use crate::color::{HdrColor, Rgba};
+use crate::fragment::Fragment;
+use crate::primitives::SPHERE_RADIUS;
+use crate::shader_registry::FragmentShader;
+use crate::shaders::Uniforms;
+
+use nalgebra_glm::{Vec3, Vec4};
+use std::f32::consts::PI;
+
+// ============================================
+// ATMÓSFERA (dispersión simple de Rayleigh y Mie)
+// ============================================
+
+// Muestras a lo largo del rayo de la cámara y, desde cada una, hacia la luz
+const VIEW_SAMPLES: usize = 10;
+const LIGHT_SAMPLES: usize = 4;
+
+// Mie también absorbe: su extinción es algo mayor que su dispersión
+const MIE_EXTINCTION: f32 = 1.1;
+
+// Parámetros de una atmósfera, en unidades del radio del planeta (la
+// superficie está en 1): así se escala junto con su cuerpo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    // Radio de la cáscara que se dibuja (1.15 = 15% sobre la superficie)
+    pub radius: f32,
+    // Coeficiente de dispersión de Rayleigh por canal: más en azul que en rojo
+    pub rayleigh: HdrColor,
+    // Altura en la que la densidad cae a 1/e
+    pub rayleigh_scale_height: f32,
+    // Dispersión de Mie (aerosoles), igual para todos los canales
+    pub mie: f32,
+    pub mie_scale_height: f32,
+    // Asimetría de Mie: 0 reparte igual en todas direcciones, cerca de 1
+    // concentra la luz hacia adelante (el halo alrededor del sol)
+    pub mie_g: f32,
+    // Multiplica la luz que llega a la atmósfera
+    pub intensity: f32,
+}
+
+impl Atmosphere {
+    // Cielo azul y atardeceres rojizos: el espesor óptico vertical es el de la
+    // Tierra, pero con una atmósfera exagerada para que se vea a esta escala
+    pub fn earth() -> Self {
+        Atmosphere {
+            radius: 1.15,
+            rayleigh: HdrColor::new(1.16, 2.7, 6.6),
+            rayleigh_scale_height: 0.04,
+            mie: 2.1,
+            mie_scale_height: 0.012,
+            mie_g: 0.76,
+            intensity: 6.0,
+        }
+    }
+
+    // Más alta y turquesa, casi sin aerosoles
+    pub fn neptune() -> Self {
+        Atmosphere {
+            radius: 1.2,
+            rayleigh: HdrColor::new(1.4, 3.6, 5.2),
+            rayleigh_scale_height: 0.06,
+            mie: 0.4,
+            mie_scale_height: 0.02,
+            mie_g: 0.7,
+            intensity: 5.0,
+        }
+    }
+
+    // Luz dispersada hacia la cámara a lo largo de un rayo (`origin` y
+    // `direction` en unidades del planeta, con el centro en el origen) y
+    // transmitancia media de lo que hay detrás. `lights` da, por luz, la
+    // dirección hacia ella y su radiancia
+    pub fn scatter(&self, origin: Vec3, direction: Vec3, lights: &[(Vec3, HdrColor)]) -> (HdrColor, f32) {
+        let Some((near, far)) = ray_sphere(origin, direction, self.radius) else {
+            return (HdrColor::black(), 1.0);
+        };
+        let near = near.max(0.0);
+        // El rayo termina en la superficie si la toca
+        let far = match ray_sphere(origin, direction, 1.0) {
+            Some((surface, _)) if surface > 0.0 => surface,
+            _ => far,
+        };
+        if far <= near {
+            return (HdrColor::black(), 1.0);
+        }
+
+        let step = (far - near) / VIEW_SAMPLES as f32;
+        let mut depth_rayleigh = 0.0;
+        let mut depth_mie = 0.0;
+        let mut sums = [(HdrColor::black(), HdrColor::black()); crate::light::MAX_LIGHTS];
+
+        for i in 0..VIEW_SAMPLES {
+            let point = origin + direction * (near + (i as f32 + 0.5) * step);
+            let height = point.magnitude() - 1.0;
+            let density_rayleigh = (-height / self.rayleigh_scale_height).exp() * step;
+            let density_mie = (-height / self.mie_scale_height).exp() * step;
+            depth_rayleigh += density_rayleigh;
+            depth_mie += density_mie;
+
+            for (sum, (to_light, _)) in sums.iter_mut().zip(lights) {
+                // A la sombra del planeta no llega luz que dispersar
+                let Some((light_rayleigh, light_mie)) = self.light_depth(point, *to_light) else {
+                    continue;
+                };
+                let attenuation = self.transmittance(depth_rayleigh + light_rayleigh, depth_mie + light_mie);
+                sum.0 += attenuation * density_rayleigh;
+                sum.1 += attenuation * density_mie;
+            }
+        }
+
+        let inscattered = lights.iter().zip(sums).fold(HdrColor::black(), |total, ((to_light, radiance), (rayleigh, mie))| {
+            let cosine = direction.dot(to_light);
+            let scattered = rayleigh * self.rayleigh * rayleigh_phase(cosine) + mie * (mie_phase(cosine, self.mie_g) * self.mie);
+            total + *radiance * scattered
+        });
+
+        let behind = self.transmittance(depth_rayleigh, depth_mie);
+        (inscattered * self.intensity, (behind.r + behind.g + behind.b) / 3.0)
+    }
+
+    // Profundidad óptica (Rayleigh, Mie) desde `point` hasta salir de la
+    // atmósfera hacia la luz; None si el planeta la tapa
+    fn light_depth(&self, point: Vec3, to_light: Vec3) -> Option<(f32, f32)> {
+        if matches!(ray_sphere(point, to_light, 1.0), Some((surface, _)) if surface > 0.0) {
+            return None;
+        }
+        let (_, exit) = ray_sphere(point, to_light, self.radius)?;
+
+        let step = exit.max(0.0) / LIGHT_SAMPLES as f32;
+        let mut depth = (0.0, 0.0);
+        for i in 0..LIGHT_SAMPLES {
+            let height = (point + to_light * ((i as f32 + 0.5) * step)).magnitude() - 1.0;
+            depth.0 += (-height / self.rayleigh_scale_height).exp() * step;
+            depth.1 += (-height / self.mie_scale_height).exp() * step;
+        }
+        Some(depth)
+    }
+
+    fn transmittance(&self, depth_rayleigh: f32, depth_mie: f32) -> HdrColor {
+        let mie = self.mie * MIE_EXTINCTION * depth_mie;
+        let channel = |rayleigh: f32| (-(rayleigh * depth_rayleigh + mie)).exp();
+        HdrColor::new(channel(self.rayleigh.r), channel(self.rayleigh.g), channel(self.rayleigh.b))
+    }
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere::earth()
+    }
+}
+
+// La cáscara es la esfera compartida (de radio SPHERE_RADIUS) escalada a
+// `radius` veces el radio del cuerpo: el centro y el radio del planeta salen
+// de su matriz de modelo. La opacidad es 1 - transmitancia, para mezclar con
+// alfa premultiplicado
+impl FragmentShader for Atmosphere {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        let center = (uniforms.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+        let scale = (uniforms.model_matrix * Vec4::new(1.0, 0.0, 0.0, 0.0)).xyz().magnitude();
+        let planet_radius = SPHERE_RADIUS * scale / self.radius;
+
+        let origin = (uniforms.camera_position - center) / planet_radius;
+        let direction = (fragment.world_position - uniforms.camera_position).normalize();
+
+        let mut lights = [(Vec3::zeros(), HdrColor::black()); crate::light::MAX_LIGHTS];
+        let count = uniforms.lights.iter().count();
+        for (slot, light) in lights.iter_mut().zip(uniforms.lights.iter()) {
+            *slot = (light.direction_from(center), light.radiance_at(center));
+        }
+
+        let (inscattered, transmittance) = self.scatter(origin, direction, &lights[..count]);
+        Rgba::new(inscattered, 1.0 - transmittance)
+    }
+}
+
+// Distancias de entrada y salida de un rayo en una esfera centrada en el origen
+fn ray_sphere(origin: Vec3, direction: Vec3, radius: f32) -> Option<(f32, f32)> {
+    let b = origin.dot(&direction);
+    let c = origin.dot(&origin) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let far = -b + root;
+    if far < 0.0 {
+        return None;
+    }
+    Some((-b - root, far))
+}
+
+fn rayleigh_phase(cosine: f32) -> f32 {
+    3.0 / (16.0 * PI) * (1.0 + cosine * cosine)
+}
+
+// Cornette-Shanks: Henyey-Greenstein corregida para acercarse a Mie real
+fn mie_phase(cosine: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    let denominator = (2.0 + g2) * (1.0 + g2 - 2.0 * g * cosine).max(1e-4).powf(1.5);
+    3.0 / (8.0 * PI) * (1.0 - g2) * (1.0 + cosine * cosine) / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lit_limb_is_blue_and_shadowed_side_is_dark() {
+        let atmosphere = Atmosphere::earth();
+        let sun = [(Vec3::new(1.0, 0.0, 0.0), HdrColor::new(1.0, 1.0, 1.0))];
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+
+        // Rayo que roza el limbo del lado iluminado, sin tocar la superficie
+        let toward_lit_limb = (Vec3::new(1.05, 0.0, 0.0) - camera).normalize();
+        let (lit, transmittance) = atmosphere.scatter(camera, toward_lit_limb, &sun);
+        assert!(lit.b > lit.r, "el halo debería ser azul: {:?}", lit);
+        assert!(transmittance > 0.0 && transmittance < 1.0);
+
+        // El mismo rayo del lado de la noche casi no recibe luz
+        let toward_dark_limb = (Vec3::new(-1.05, 0.0, 0.0) - camera).normalize();
+        let (dark, _) = atmosphere.scatter(camera, toward_dark_limb, &sun);
+        assert!(dark.luminance() < lit.luminance() * 0.2);
+
+        // Fuera de la cáscara no hay nada
+        let outside = (Vec3::new(2.0, 0.0, 0.0) - camera).normalize();
+        assert_eq!(atmosphere.scatter(camera, outside, &sun), (HdrColor::black(), 1.0));
+    }
+
+    #[test]
+    fn denser_air_scatters_more_toward_the_camera() {
+        let sun = [(Vec3::new(0.0, 0.0, 1.0), HdrColor::new(1.0, 1.0, 1.0))];
+        let camera = Vec3::new(0.0, 0.0, 10.0);
+        let toward_limb = (Vec3::new(1.08, 0.0, 0.0) - camera).normalize();
+
+        let thin = Atmosphere { rayleigh_scale_height: 0.02, ..Atmosphere::earth() };
+        let thick = Atmosphere { rayleigh_scale_height: 0.08, ..Atmosphere::earth() };
+        let (thin_light, thin_transmittance) = thin.scatter(camera, toward_limb, &sun);
+        let (thick_light, thick_transmittance) = thick.scatter(camera, toward_limb, &sun);
+        assert!(thick_light.luminance() > thin_light.luminance());
+        assert!(thick_transmittance < thin_transmittance);
+    }
+}
@@ -1,6 +1,9 @@
 use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
 use std::f32::consts::PI;
 
+// Distancia al plano cercano: lo que esté más cerca de la cámara se recorta
+pub const NEAR_PLANE: f32 = 0.1;
+
 pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
@@ -18,19 +21,6 @@ impl Camera {
         }
     }
 
-    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
-        let forward = nalgebra_glm::normalize(&(self.center - self.eye));
-        let right = nalgebra_glm::normalize(&nalgebra_glm::cross(&forward, &self.up));
-        let up = nalgebra_glm::cross(&right, &forward);
-
-        let rotated = 
-            right * vector.x +
-            up * vector.y +
-            (-forward) * vector.z;
-
-        rotated.normalize()
-    }
-
     pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
         let radius_vector = self.eye - self.center;
         let radius = radius_vector.magnitude();
@@ -63,6 +53,17 @@ impl Camera {
     }
 
     pub fn get_projection_matrix(&self, aspect: f32) -> Mat4 {
-        perspective(aspect, PI / 4.0, 0.1, 1000.0)
+        perspective(aspect, PI / 4.0, NEAR_PLANE, 1000.0)
     }
-}
\ No newline at end of file
+}
+
+// Cámara inicial: cerca del planeta o alejada para ver todo el sistema
+pub fn default_camera(system_view: bool) -> Camera {
+    let eye = if system_view {
+        Vec3::new(0.0, 12.0, 26.0)
+    } else {
+        Vec3::new(0.0, 0.0, 4.5)
+    };
+
+    Camera::new(eye, Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+}
@@ -0,0 +1,186 @@
+use crate::triangle::Triangle;
+use crate::vertex::Vertex;
+
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+// Margen (en múltiplos del frustum) dentro del cual no se recortan los lados:
+// el rasterizador ya limita la caja envolvente a la pantalla, así que solo
+// hace falta recortar lo que podría desbordar las coordenadas enteras
+pub const GUARD_BAND: f32 = 4.0;
+
+// Planos en espacio de clip; un vértice es visible si dot(plano, posición) >= 0
+const CLIP_PLANES: [[f32; 4]; 6] = [
+    [0.0, 0.0, 1.0, 1.0],          // cercano: z >= -w
+    [0.0, 0.0, -1.0, 1.0],         // lejano: z <= w
+    [1.0, 0.0, 0.0, GUARD_BAND],   // izquierdo
+    [-1.0, 0.0, 0.0, GUARD_BAND],  // derecho
+    [0.0, 1.0, 0.0, GUARD_BAND],   // inferior
+    [0.0, -1.0, 0.0, GUARD_BAND],  // superior
+];
+
+// Recorta un triángulo (vértices en espacio de clip) contra el frustum,
+// proyecta el resultado a pantalla y agrega los triángulos a `out`
+pub fn clip_triangle(vertices: [&Vertex; 3], viewport: &Mat4, out: &mut Vec<Triangle>) {
+    let outcodes = vertices.map(outcode);
+
+    // Todos los vértices fuera de un mismo plano: no hay nada visible
+    if outcodes[0] & outcodes[1] & outcodes[2] != 0 {
+        return;
+    }
+
+    // Caso común: el triángulo entero está dentro
+    let crossed = outcodes[0] | outcodes[1] | outcodes[2];
+    if crossed == 0 {
+        out.push(Triangle::new_from_vertices(
+            project(vertices[0], viewport),
+            project(vertices[1], viewport),
+            project(vertices[2], viewport),
+        ));
+        return;
+    }
+
+    // Sutherland-Hodgman, solo contra los planos que el triángulo cruza
+    let mut polygon: Vec<Vertex> = vertices.iter().map(|v| (*v).clone()).collect();
+    for (i, plane) in CLIP_PLANES.iter().enumerate() {
+        if crossed & (1 << i) == 0 {
+            continue;
+        }
+
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (j, current) in polygon.iter().enumerate() {
+            let next = &polygon[(j + 1) % polygon.len()];
+            let d_current = plane_distance(plane, &current.clip_position);
+            let d_next = plane_distance(plane, &next.clip_position);
+
+            if d_current >= 0.0 {
+                clipped.push(current.clone());
+            }
+            if (d_current >= 0.0) != (d_next >= 0.0) {
+                let t = d_current / (d_current - d_next);
+                clipped.push(lerp_vertex(current, next, t));
+            }
+        }
+
+        polygon = clipped;
+        if polygon.len() < 3 {
+            return;
+        }
+    }
+
+    // El polígono recortado es convexo: se triangula en abanico
+    let projected: Vec<Vertex> = polygon.iter().map(|v| project(v, viewport)).collect();
+    for i in 1..projected.len() - 1 {
+        out.push(Triangle::new_from_vertices(
+            projected[0].clone(),
+            projected[i].clone(),
+            projected[i + 1].clone(),
+        ));
+    }
+}
+
+// División por w y transformación de viewport
+pub fn project(vertex: &Vertex, viewport: &Mat4) -> Vertex {
+    let clip = vertex.clip_position;
+    let w = clip.w;
+    let ndc = Vec4::new(clip.x / w, clip.y / w, clip.z / w, 1.0);
+    let screen = viewport * ndc;
+
+    let mut projected = vertex.clone();
+    projected.transformed_position = Vec3::new(screen.x, screen.y, screen.z);
+    projected.inv_w = 1.0 / w;
+    projected
+}
+
+fn outcode(vertex: &Vertex) -> u8 {
+    CLIP_PLANES
+        .iter()
+        .enumerate()
+        .filter(|(_, plane)| plane_distance(plane, &vertex.clip_position) < 0.0)
+        .fold(0, |code, (i, _)| code | (1 << i))
+}
+
+fn plane_distance(plane: &[f32; 4], position: &Vec4) -> f32 {
+    plane[0] * position.x + plane[1] * position.y + plane[2] * position.z + plane[3] * position.w
+}
+
+// En espacio de clip todos los atributos varían linealmente sobre la arista
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    Vertex {
+        position: a.position.lerp(&b.position, t),
+        normal: a.normal.lerp(&b.normal, t),
+        tex_coords: a.tex_coords.lerp(&b.tex_coords, t),
+        tangent: a.tangent.lerp(&b.tangent, t),
+        color: a.color.lerp(&b.color, t),
+        clip_position: a.clip_position.lerp(&b.clip_position, t),
+        transformed_position: Vec3::zeros(),
+        transformed_normal: a.transformed_normal.lerp(&b.transformed_normal, t),
+        transformed_tangent: a.transformed_tangent.lerp(&b.transformed_tangent, t),
+        world_position: a.world_position.lerp(&b.world_position, t),
+        inv_w: 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec2;
+
+    fn clip_vertex(x: f32, y: f32, z: f32, w: f32, u: f32) -> Vertex {
+        let mut vertex = Vertex::new(Vec3::zeros(), Vec3::new(0.0, 0.0, 1.0), Vec2::new(u, 0.0));
+        vertex.clip_position = Vec4::new(x, y, z, w);
+        vertex
+    }
+
+    fn clip(vertices: [&Vertex; 3]) -> Vec<Triangle> {
+        let mut out = Vec::new();
+        clip_triangle(vertices, &Mat4::identity(), &mut out);
+        out
+    }
+
+    #[test]
+    fn inside_triangle_passes_through() {
+        let a = clip_vertex(-0.5, -0.5, 0.0, 1.0, 0.0);
+        let b = clip_vertex(0.5, -0.5, 0.0, 1.0, 0.5);
+        let c = clip_vertex(0.0, 0.5, 0.0, 1.0, 1.0);
+
+        let triangles = clip([&a, &b, &c]);
+        assert_eq!(triangles.len(), 1);
+        assert_eq!(triangles[0].v2.transformed_position, Vec3::new(0.5, -0.5, 0.0));
+    }
+
+    #[test]
+    fn triangle_behind_camera_is_discarded() {
+        let a = clip_vertex(-0.5, -0.5, -2.0, -1.0, 0.0);
+        let b = clip_vertex(0.5, -0.5, -2.0, -1.0, 0.0);
+        let c = clip_vertex(0.0, 0.5, -2.0, -1.0, 0.0);
+
+        assert!(clip([&a, &b, &c]).is_empty());
+    }
+
+    #[test]
+    fn near_plane_split_interpolates_attributes() {
+        // Un vértice detrás del plano cercano: quedan un cuadrilátero y dos triángulos
+        let a = clip_vertex(0.0, 0.0, -3.0, 1.0, 0.0);
+        let b = clip_vertex(-0.5, 0.0, 0.0, 1.0, 1.0);
+        let c = clip_vertex(0.5, 0.0, 1.0, 1.0, 1.0);
+
+        let triangles = clip([&a, &b, &c]);
+        assert_eq!(triangles.len(), 2);
+
+        for triangle in &triangles {
+            for vertex in [&triangle.v1, &triangle.v2, &triangle.v3] {
+                let clip = vertex.clip_position;
+                assert!(clip.z >= -clip.w - 1e-5, "vértice delante del plano cercano: {:?}", clip);
+                assert!(vertex.transformed_position.z >= -1.0 - 1e-5);
+            }
+        }
+
+        // Sobre la arista a-b el corte está en z = -w, es decir t = 2/3
+        let cut = triangles
+            .iter()
+            .flat_map(|t| [&t.v1, &t.v2, &t.v3])
+            .find(|v| (v.clip_position.x + 1.0 / 3.0).abs() < 1e-5)
+            .expect("falta el vértice de corte en a-b");
+        assert!((cut.tex_coords.x - 2.0 / 3.0).abs() < 1e-5);
+    }
+}
@@ -1,5 +1,5 @@
 use std::fmt;
-use std::ops::{Add, Mul};
+use std::ops::{Add, AddAssign, Mul, Sub};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -17,10 +17,6 @@ impl Color {
         Color { r: 0, g: 0, b: 0 }
     }
 
-    pub fn white() -> Self {
-        Color { r: 255, g: 255, b: 255 }
-    }
-
     pub fn from_float(r: f32, g: f32, b: f32) -> Self {
         Color {
             r: (r.clamp(0.0, 1.0) * 255.0) as u8,
@@ -36,7 +32,7 @@ impl Color {
         Color { r, g, b }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
@@ -78,4 +74,135 @@ impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Color({}, {}, {})", self.r, self.g, self.b)
     }
-}
\ No newline at end of file
+}
+
+// ============================================
+// COLOR HDR (lineal, sin límite superior)
+// ============================================
+
+// Radiancia lineal en punto flotante: los shaders pueden sumar brillos por
+// encima de 1.0 y el tone mapping decide al final cómo comprimirlos
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HdrColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl HdrColor {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        HdrColor { r, g, b }
+    }
+
+    pub fn black() -> Self {
+        HdrColor::default()
+    }
+
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn lerp(&self, other: &HdrColor, t: f32) -> HdrColor {
+        *self * (1.0 - t) + *other * t
+    }
+
+    pub fn map(self, f: impl Fn(f32) -> f32) -> HdrColor {
+        HdrColor::new(f(self.r), f(self.g), f(self.b))
+    }
+}
+
+// Los colores de 8 bits están en sRGB: se decodifican a lineal
+impl From<Color> for HdrColor {
+    fn from(color: Color) -> Self {
+        HdrColor::new(
+            srgb_to_linear(color.r as f32 / 255.0),
+            srgb_to_linear(color.g as f32 / 255.0),
+            srgb_to_linear(color.b as f32 / 255.0),
+        )
+    }
+}
+
+impl Add for HdrColor {
+    type Output = HdrColor;
+
+    fn add(self, other: HdrColor) -> HdrColor {
+        HdrColor::new(self.r + other.r, self.g + other.g, self.b + other.b)
+    }
+}
+
+impl Sub for HdrColor {
+    type Output = HdrColor;
+
+    fn sub(self, other: HdrColor) -> HdrColor {
+        HdrColor::new(self.r - other.r, self.g - other.g, self.b - other.b)
+    }
+}
+
+impl AddAssign for HdrColor {
+    fn add_assign(&mut self, other: HdrColor) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<f32> for HdrColor {
+    type Output = HdrColor;
+
+    fn mul(self, scalar: f32) -> HdrColor {
+        HdrColor::new(self.r * scalar, self.g * scalar, self.b * scalar)
+    }
+}
+
+impl Mul for HdrColor {
+    type Output = HdrColor;
+
+    fn mul(self, other: HdrColor) -> HdrColor {
+        HdrColor::new(self.r * other.r, self.g * other.g, self.b * other.b)
+    }
+}
+
+// Color HDR con opacidad: lo que devuelve un fragment shader. La opacidad
+// solo importa en draws con mezcla (ver `BlendMode`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub color: HdrColor,
+    pub alpha: f32,
+}
+
+impl Rgba {
+    pub fn new(color: HdrColor, alpha: f32) -> Self {
+        Rgba { color, alpha }
+    }
+
+    pub fn opaque(color: HdrColor) -> Self {
+        Rgba { color, alpha: 1.0 }
+    }
+}
+
+impl From<HdrColor> for Rgba {
+    fn from(color: HdrColor) -> Self {
+        Rgba::opaque(color)
+    }
+}
+
+impl From<Color> for Rgba {
+    fn from(color: Color) -> Self {
+        Rgba::opaque(HdrColor::from(color))
+    }
+}
+
+// Curvas exactas de sRGB (IEC 61966-2-1), con el tramo lineal cerca del negro
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -1,33 +1,25 @@
-use nalgebra_glm::Vec2;
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
 #[derive(Clone, Debug)]
 pub struct Fragment {
-    pub position: Vec2,
     pub color: Color,
-    pub depth: f32,
-    pub normal: nalgebra_glm::Vec3,
-    pub intensity: f32,
+    pub normal: Vec3,
+    pub tex_coords: Vec2,
+    // Tangente en espacio de mundo; w = +-1 indica la orientación de la bitangente
+    pub tangent: Vec4,
+    // Posición interpolada en espacio de mundo
+    pub world_position: Vec3,
 }
 
 impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32) -> Self {
+    pub fn new(color: Color, normal: Vec3) -> Self {
         Fragment {
-            position: Vec2::new(x, y),
             color,
-            depth,
-            normal: nalgebra_glm::Vec3::new(0.0, 0.0, 1.0),
-            intensity: 1.0,
-        }
-    }
-
-    pub fn new_with_normal(x: f32, y: f32, color: Color, depth: f32, normal: nalgebra_glm::Vec3, intensity: f32) -> Self {
-        Fragment {
-            position: Vec2::new(x, y),
-            color,
-            depth,
             normal,
-            intensity,
+            tex_coords: Vec2::new(0.0, 0.0),
+            tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            world_position: Vec3::new(0.0, 0.0, 0.0),
         }
     }
-}
\ No newline at end of file
+}
@@ -1,14 +1,19 @@
-use crate::color::Color;
+use crate::color::{Color, HdrColor};
+use crate::tonemap::ToneMapping;
 
 pub const SCREEN_WIDTH: usize = 800;
 pub const SCREEN_HEIGHT: usize = 600;
 
+// Los shaders acumulan en `hdr_buffer` (lineal, en punto flotante); `resolve`
+// lo pasa por el tone mapping a `buffer`, que es el que se presenta o guarda
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
+    pub hdr_buffer: Vec<HdrColor>,
     pub zbuffer: Vec<f32>,
-    pub background_color: Color,
+    // Radiancia del fondo: pasa por el tone mapping como el resto de la imagen
+    pub background_color: HdrColor,
     pub current_color: Color,
 }
 
@@ -18,38 +23,61 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            hdr_buffer: vec![HdrColor::black(); width * height],
             zbuffer: vec![f32::INFINITY; width * height],
-            background_color: Color::black(),
+            background_color: HdrColor::black(),
             current_color: Color::new(255, 255, 255),
         }
     }
 
     pub fn clear(&mut self) {
-        let bg_color = self.background_color.to_hex();
         for pixel in self.buffer.iter_mut() {
-            *pixel = bg_color;
+            *pixel = 0;
+        }
+        for pixel in self.hdr_buffer.iter_mut() {
+            *pixel = self.background_color;
         }
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
-        self.current_color = color;
-    }
-
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
-            let index = y * self.width + x;
-            
-            if depth < self.zbuffer[index] {
-                self.buffer[index] = self.current_color.to_hex();
-                self.zbuffer[index] = depth;
-            }
+    // Convierte el buffer HDR al de presentación con el tone mapping dado. Todo
+    // pasa por la misma curva, haya geometría o no: las capas translúcidas
+    // sobre el fondo no dejan costura en la silueta y también pueden oscurecerlo
+    pub fn resolve(&mut self, tone_mapping: &ToneMapping) {
+        for (pixel, color) in self.buffer.iter_mut().zip(&self.hdr_buffer) {
+            *pixel = tone_mapping.map(*color).to_hex();
         }
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
+    pub fn set_background_color(&mut self, color: HdrColor) {
         self.background_color = color;
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_and_covered_pixels_share_the_tone_curve() {
+        let tone_mapping = ToneMapping::default();
+        let mut framebuffer = Framebuffer::new(3, 1);
+        framebuffer.set_background_color(HdrColor::new(0.5, 0.5, 0.5));
+        framebuffer.clear();
+
+        // Mismo color con y sin profundidad escrita, y una capa que oscurece el fondo
+        let layer = HdrColor::new(0.8, 0.4, 0.1);
+        framebuffer.hdr_buffer[0] = layer;
+        framebuffer.zbuffer[0] = 0.5;
+        framebuffer.hdr_buffer[1] = layer;
+        framebuffer.hdr_buffer[2] = HdrColor::new(0.1, 0.1, 0.1);
+        framebuffer.resolve(&tone_mapping);
+
+        assert_eq!(framebuffer.buffer[0], framebuffer.buffer[1]);
+        assert_eq!(framebuffer.buffer[1], tone_mapping.map(layer).to_hex());
+        let background = tone_mapping.map(framebuffer.background_color);
+        assert!(Color::from_hex(framebuffer.buffer[2]).r < background.r);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::color::{HdrColor, Rgba};
+use crate::fragment::Fragment;
+use crate::primitives::SPHERE_RADIUS;
+use crate::shader_registry::FragmentShader;
+use crate::shaders::Uniforms;
+
+use nalgebra_glm::Vec4;
+
+// ============================================
+// RESPLANDOR (halo de un cuerpo emisor)
+// ============================================
+
+// Halo alrededor de un cuerpo que emite luz, como el sol. Se dibuja en una
+// cáscara que se mezcla con BlendMode::Additive: solo agrega luz, así que no
+// tapa ni oscurece nada de lo que hay detrás
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glow {
+    // Radio de la cáscara en radios del cuerpo: más allá el halo se apaga
+    pub radius: f32,
+    pub color: HdrColor,
+    pub intensity: f32,
+}
+
+impl Glow {
+    // Halo cálido que se extiende un 60% más allá del disco
+    pub fn sun() -> Self {
+        Glow {
+            radius: 1.6,
+            color: HdrColor::new(1.0, 0.6, 0.25),
+            intensity: 0.8,
+        }
+    }
+
+    // Fracción del halo para un rayo que pasa a `distance` radios del centro:
+    // cae como 1/d² desde el limbo y llega a 0 en el borde de la cáscara
+    pub fn falloff(&self, distance: f32) -> f32 {
+        let edge = ((self.radius - distance) / (self.radius - 1.0)).clamp(0.0, 1.0);
+        edge * edge / distance.max(1.0).powi(2)
+    }
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Glow::sun()
+    }
+}
+
+// Como la atmósfera, la cáscara es la esfera compartida escalada a `radius`
+// veces el radio del cuerpo. Lo que importa es cuánto se acerca al centro el
+// rayo de la cámara, no en qué punto de la cáscara cae el fragmento
+impl FragmentShader for Glow {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        let center = (uniforms.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+        let scale = (uniforms.model_matrix * Vec4::new(1.0, 0.0, 0.0, 0.0)).xyz().magnitude();
+        let body_radius = SPHERE_RADIUS * scale / self.radius;
+
+        let direction = (fragment.world_position - uniforms.camera_position).normalize();
+        let to_center = center - uniforms.camera_position;
+        let along = to_center.dot(&direction).max(0.0);
+        let distance = (to_center - direction * along).magnitude() / body_radius;
+
+        Rgba::new(self.color * (self.intensity * self.falloff(distance)), 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halo_fades_from_the_limb_to_the_shell() {
+        let glow = Glow::sun();
+
+        assert_eq!(glow.falloff(0.5), 1.0);
+        assert_eq!(glow.falloff(1.0), 1.0);
+        assert!(glow.falloff(1.2) < glow.falloff(1.1));
+        assert!(glow.falloff(1.2) > 0.0);
+        assert_eq!(glow.falloff(glow.radius), 0.0);
+        assert_eq!(glow.falloff(3.0), 0.0);
+    }
+}
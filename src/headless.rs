@@ -0,0 +1,405 @@
+use crate::framebuffer::{Framebuffer, SCREEN_WIDTH, SCREEN_HEIGHT};
+use crate::color::Color;
+use crate::renderer::{render_planet_view, render_system_view, SceneAssets};
+use crate::scene::{Scene, MAX_BLOOM_RADIUS};
+use crate::camera::default_camera;
+use crate::lod::LodState;
+use crate::tonemap::ToneOperator;
+use crate::shadow::ShadowMethod;
+
+use nalgebra_glm::Vec3;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// Alias aceptados en la línea de comandos y el shader que usan; cualquier
+// otro nombre se busca directamente en el registro de shaders
+const PLANET_NAMES: &[(&str, &str)] = &[
+    ("sol", "sun"),
+    ("sun", "sun"),
+    ("marte", "rocky_mars"),
+    ("mars", "rocky_mars"),
+    ("tierra", "rocky_earth"),
+    ("earth", "rocky_earth"),
+    ("jupiter", "gas_jupiter"),
+    ("saturno", "gas_saturn"),
+    ("saturn", "gas_saturn"),
+    ("neptuno", "ice_neptune"),
+    ("neptune", "ice_neptune"),
+    ("test", "test"),
+    ("sistema", "system"),
+    ("system", "system"),
+];
+
+// Por lado: el framebuffer reserva unos 24 bytes por píxel entre color HDR,
+// profundidad y presentación, así que 8192² ya son más de 1.5 GB
+const MAX_RESOLUTION: usize = 8192;
+
+pub const USAGE: &str = "\
+Uso: proyecto2 --headless [opciones]
+     proyecto2 --list-shaders
+
+Opciones:
+  --scene <ruta>      Escena TOML con cuerpos, lunas, anillos y luz
+                      (default: scenes/sistema_solar.toml si existe)
+  --planet <nombre>   sol, marte, tierra, jupiter, saturno, neptuno, sistema, test
+                      o el nombre de cualquier shader registrado (default: tierra)
+  --camera <x,y,z>    Posición de la cámara (default: 0,0,4.5; sistema: 0,12,26)
+  --time <segundos>   Tiempo de animación del primer frame (default: 0)
+  --width <px>        Ancho de la imagen, hasta 8192 (default: 800)
+  --height <px>       Alto de la imagen, hasta 8192 (default: 600)
+  --frames <n>        Cantidad de frames a generar (default: 1)
+  --fps <n>           Frames por segundo de la secuencia (default: 30)
+  --output <ruta>     Archivo de salida .png o .ppm (default: frame.png)
+  --tone-map <op>     Tone mapping: none, reinhard, aces o exposure
+                      (default: el de la escena, si no aces)
+  --exposure <valor>  Exposición antes del tone mapping (default: la de la escena, si no 1)
+  --bloom <valor>     Intensidad del bloom; 0 lo desactiva (default: la de la escena)
+  --bloom-radius <px> Radio del desenfoque del bloom, hasta 128
+                      (default: el de la escena, si no 24)
+  --shadows <método>  Sombras entre cuerpos: none, analytic o map
+                      (default: el de la escena, si no analytic)
+  --lod-overlay       Marca cada cuerpo con su nivel de detalle";
+
+pub struct HeadlessOptions {
+    pub shader_type: String,
+    pub camera_eye: Option<Vec3>,
+    pub time: f32,
+    pub width: usize,
+    pub height: usize,
+    pub frames: u32,
+    pub fps: f32,
+    pub output: String,
+    pub scene_path: Option<String>,
+    pub lod_overlay: bool,
+    pub tone_operator: Option<ToneOperator>,
+    pub exposure: Option<f32>,
+    pub bloom_intensity: Option<f32>,
+    pub bloom_radius: Option<usize>,
+    pub shadow_method: Option<ShadowMethod>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        HeadlessOptions {
+            shader_type: "rocky_earth".to_string(),
+            camera_eye: None,
+            time: 0.0,
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            frames: 1,
+            fps: 30.0,
+            output: "frame.png".to_string(),
+            scene_path: None,
+            lod_overlay: false,
+            tone_operator: None,
+            exposure: None,
+            bloom_intensity: None,
+            bloom_radius: None,
+            shadow_method: None,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut options = HeadlessOptions::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if arg == "--headless" {
+                continue;
+            }
+            if arg == "--lod-overlay" {
+                options.lod_overlay = true;
+                continue;
+            }
+
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Falta el valor para {}", arg))?;
+
+            match arg.as_str() {
+                "--planet" => options.shader_type = parse_planet(value),
+                "--camera" => options.camera_eye = Some(parse_vec3(value)?),
+                "--time" => options.time = parse_number(arg, value)?,
+                "--width" => options.width = parse_number(arg, value)?,
+                "--height" => options.height = parse_number(arg, value)?,
+                "--frames" => options.frames = parse_number(arg, value)?,
+                "--fps" => options.fps = parse_number(arg, value)?,
+                "--output" => options.output = value.clone(),
+                "--scene" => options.scene_path = Some(value.clone()),
+                "--tone-map" => options.tone_operator = Some(ToneOperator::parse(value)?),
+                "--exposure" => options.exposure = Some(parse_number(arg, value)?),
+                "--bloom" => options.bloom_intensity = Some(parse_number(arg, value)?),
+                "--bloom-radius" => options.bloom_radius = Some(parse_number(arg, value)?),
+                "--shadows" => options.shadow_method = Some(ShadowMethod::parse(value)?),
+                _ => return Err(format!("Opción desconocida: {}", arg)),
+            }
+        }
+
+        if options.width == 0 || options.height == 0 {
+            return Err("La resolución debe ser mayor que 0".to_string());
+        }
+        if options.width > MAX_RESOLUTION || options.height > MAX_RESOLUTION {
+            return Err(format!(
+                "La resolución admite hasta {} píxeles por lado (se obtuvo {}x{})",
+                MAX_RESOLUTION, options.width, options.height
+            ));
+        }
+        // Mejor fallar antes de renderizar que después del primer frame
+        if !matches!(image_extension(&options.output).as_deref(), Some("png" | "ppm")) {
+            return Err(format!("--output debe terminar en .png o .ppm (se obtuvo {})", options.output));
+        }
+        if options.frames == 0 {
+            return Err("--frames debe ser mayor que 0".to_string());
+        }
+        // Un NaN pasaría una comparación como `fps <= 0.0` y arruinaría el tiempo de cada frame
+        if !options.fps.is_finite() || options.fps <= 0.0 {
+            return Err("--fps debe ser mayor que 0".to_string());
+        }
+        if !options.time.is_finite() {
+            return Err("--time debe ser un número finito".to_string());
+        }
+        if options.exposure.is_some_and(|exposure| !exposure.is_finite() || exposure <= 0.0) {
+            return Err("--exposure debe ser mayor que 0".to_string());
+        }
+        if options.bloom_intensity.is_some_and(|intensity| !intensity.is_finite() || intensity < 0.0) {
+            return Err("--bloom no puede ser negativo".to_string());
+        }
+        // El kernel del desenfoque tiene 2 * radio + 1 pesos: sin tope se puede agotar la memoria
+        if let Some(radius) = options.bloom_radius.filter(|radius| *radius > MAX_BLOOM_RADIUS) {
+            return Err(format!("--bloom-radius admite hasta {} píxeles (se obtuvo {})", MAX_BLOOM_RADIUS, radius));
+        }
+
+        Ok(options)
+    }
+
+    // Ruta del frame i; con un solo frame se usa la ruta tal cual
+    fn frame_path(&self, index: u32) -> String {
+        if self.frames == 1 {
+            return self.output.clone();
+        }
+
+        let path = Path::new(&self.output);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("png");
+        let file_name = format!("{}_{:04}.{}", stem, index, extension);
+
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => {
+                parent.join(file_name).to_string_lossy().into_owned()
+            }
+            _ => file_name,
+        }
+    }
+}
+
+pub fn run(options: &HeadlessOptions, assets: &SceneAssets, scene: &mut Scene) -> Result<(), String> {
+    let mut framebuffer = Framebuffer::new(options.width, options.height);
+
+    let system_view = options.shader_type == "system";
+    let mut camera = default_camera(system_view);
+    if let Some(eye) = options.camera_eye {
+        camera.eye = eye;
+    }
+    let rotation = Vec3::new(0.0, 0.0, 0.0);
+
+    // La línea de comandos tiene prioridad sobre el tone mapping de la escena
+    if let Some(operator) = options.tone_operator {
+        scene.tone_mapping.operator = operator;
+    }
+    if let Some(exposure) = options.exposure {
+        scene.tone_mapping.exposure = exposure;
+    }
+    if let Some(intensity) = options.bloom_intensity {
+        scene.post_processing.bloom.enabled = intensity > 0.0;
+        scene.post_processing.bloom.intensity = intensity;
+    }
+    if let Some(radius) = options.bloom_radius {
+        scene.post_processing.bloom.radius = radius;
+    }
+    if let Some(method) = options.shadow_method {
+        scene.shadows = method;
+    }
+
+    // Se conserva entre frames para que la histéresis evite saltos de nivel
+    let mut lod = LodState::new();
+    lod.show_overlay = options.lod_overlay;
+
+    for frame in 0..options.frames {
+        let time = options.time + frame as f32 / options.fps;
+
+        if system_view {
+            // Las órbitas se calculan desde t = 0 para que cada frame sea reproducible
+            scene.reset();
+            scene.update(time);
+            render_system_view(&mut framebuffer, assets, &mut lod, scene, &camera, time)?;
+        } else {
+            render_planet_view(&mut framebuffer, assets, &mut lod, scene, &camera, &options.shader_type, rotation, time)?;
+        }
+
+        let path = options.frame_path(frame);
+        save_framebuffer(&framebuffer, &path)?;
+        println!("🖼️  Frame guardado: {}", path);
+    }
+
+    Ok(())
+}
+
+// Guarda el framebuffer según la extensión del archivo (.png o .ppm)
+pub fn save_framebuffer(framebuffer: &Framebuffer, path: &str) -> Result<(), String> {
+    match image_extension(path).as_deref() {
+        Some("png") => write_png(framebuffer, path),
+        Some("ppm") => write_ppm(framebuffer, path),
+        _ => Err(format!("Formato de imagen no soportado: {}", path)),
+    }
+}
+
+fn image_extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase())
+}
+
+fn to_rgb_bytes(framebuffer: &Framebuffer) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(framebuffer.buffer.len() * 3);
+    for &pixel in &framebuffer.buffer {
+        let color = Color::from_hex(pixel);
+        bytes.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+    bytes
+}
+
+fn write_png(framebuffer: &Framebuffer, path: &str) -> Result<(), String> {
+    image::save_buffer_with_format(
+        path,
+        &to_rgb_bytes(framebuffer),
+        framebuffer.width as u32,
+        framebuffer.height as u32,
+        image::ColorType::Rgb8,
+        image::ImageFormat::Png,
+    )
+    .map_err(|e| format!("Error escribiendo {}: {}", path, e))
+}
+
+fn write_ppm(framebuffer: &Framebuffer, path: &str) -> Result<(), String> {
+    let file = File::create(path)
+        .map_err(|e| format!("Error creando {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+
+    write!(writer, "P6\n{} {}\n255\n", framebuffer.width, framebuffer.height)
+        .and_then(|_| writer.write_all(&to_rgb_bytes(framebuffer)))
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Error escribiendo {}: {}", path, e))
+}
+
+fn parse_planet(value: &str) -> String {
+    let name = value.to_lowercase();
+    PLANET_NAMES
+        .iter()
+        .find(|(alias, shader)| *alias == name || *shader == name)
+        .map(|(_, shader)| shader.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3, String> {
+    let components: Vec<f32> = value
+        .split(',')
+        .map(|c| c.trim().parse::<f32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Vector inválido: {} (se esperaba x,y,z)", value))?;
+
+    match components.as_slice() {
+        [x, y, z] if components.iter().all(|c| c.is_finite()) => Ok(Vec3::new(*x, *y, *z)),
+        _ => Err(format!("Vector inválido: {} (se esperaba x,y,z)", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Valor inválido para {}: {}", option, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<HeadlessOptions, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        HeadlessOptions::from_args(&args)
+    }
+
+    #[test]
+    fn arguments_override_the_defaults() {
+        let options = parse(&[
+            "--headless", "--planet", "Saturno", "--camera", "1, 2.5,-3", "--time", "1.5",
+            "--frames", "4", "--fps", "24", "--output", "out/seq.ppm", "--tone-map", "reinhard",
+            "--shadows", "map", "--lod-overlay",
+        ])
+        .unwrap();
+
+        assert_eq!(options.shader_type, "gas_saturn");
+        assert_eq!(options.camera_eye, Some(Vec3::new(1.0, 2.5, -3.0)));
+        assert_eq!((options.time, options.frames, options.fps), (1.5, 4, 24.0));
+        assert_eq!(options.output, "out/seq.ppm");
+        assert_eq!(options.tone_operator, Some(ToneOperator::Reinhard));
+        assert_eq!(options.shadow_method, Some(ShadowMethod::Map));
+        assert!(options.lod_overlay);
+
+        // Un nombre que no es alias se pasa tal cual al registro de shaders
+        assert_eq!(parse(&["--planet", "textured"]).unwrap().shader_type, "textured");
+        assert_eq!(parse(&[]).unwrap().shader_type, "rocky_earth");
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        let error = |args: &[&str]| parse(args).err().unwrap();
+
+        assert_eq!(error(&["--nope", "1"]), "Opción desconocida: --nope");
+        assert_eq!(error(&["--width"]), "Falta el valor para --width");
+        assert_eq!(error(&["--width", "ancho"]), "Valor inválido para --width: ancho");
+        assert_eq!(error(&["--height", "0"]), "La resolución debe ser mayor que 0");
+        assert_eq!(
+            error(&["--width", "100000"]),
+            "La resolución admite hasta 8192 píxeles por lado (se obtuvo 100000x600)"
+        );
+        assert!(parse(&["--width", "8192", "--height", "8192"]).is_ok());
+        assert_eq!(error(&["--output", "frame.jpg"]), "--output debe terminar en .png o .ppm (se obtuvo frame.jpg)");
+        assert_eq!(error(&["--output", "frame"]), "--output debe terminar en .png o .ppm (se obtuvo frame)");
+        assert!(parse(&["--output", "FRAME.PNG"]).is_ok());
+        assert_eq!(error(&["--frames", "0"]), "--frames debe ser mayor que 0");
+        assert_eq!(error(&["--camera", "1,2"]), "Vector inválido: 1,2 (se esperaba x,y,z)");
+        assert_eq!(error(&["--camera", "1,nan,2"]), "Vector inválido: 1,nan,2 (se esperaba x,y,z)");
+        assert_eq!(error(&["--exposure", "0"]), "--exposure debe ser mayor que 0");
+        assert_eq!(error(&["--bloom", "-1"]), "--bloom no puede ser negativo");
+        assert_eq!(
+            error(&["--bloom-radius", "1000000000"]),
+            format!("--bloom-radius admite hasta {} píxeles (se obtuvo 1000000000)", MAX_BLOOM_RADIUS)
+        );
+        assert!(parse(&["--bloom-radius", &MAX_BLOOM_RADIUS.to_string()]).is_ok());
+
+        // Los no finitos no pasan las comparaciones, así que se revisan aparte
+        for fps in ["0", "-30", "nan", "inf"] {
+            assert_eq!(error(&["--fps", fps]), "--fps debe ser mayor que 0");
+        }
+        for time in ["nan", "inf", "-inf"] {
+            assert_eq!(error(&["--time", time]), "--time debe ser un número finito");
+        }
+    }
+
+    #[test]
+    fn frames_are_numbered_next_to_the_output() {
+        let single = parse(&["--output", "render/tierra.png"]).unwrap();
+        assert_eq!(single.frame_path(0), "render/tierra.png");
+
+        let sequence = parse(&["--output", "render/tierra.ppm", "--frames", "3"]).unwrap();
+        assert_eq!(sequence.frame_path(0), "render/tierra_0000.ppm");
+        assert_eq!(sequence.frame_path(2), "render/tierra_0002.ppm");
+
+        let bare = parse(&["--output", "frame.png", "--frames", "2"]).unwrap();
+        assert_eq!(bare.frame_path(1), "frame_0001.png");
+    }
+}
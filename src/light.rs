@@ -0,0 +1,244 @@
+use nalgebra_glm::Vec3;
+use crate::color::HdrColor;
+
+// ============================================
+// LUCES DE LA ESCENA (en espacio de mundo)
+// ============================================
+
+// Máximo de luces por frame: `Lights` vive en `Uniforms`, que se copia por draw
+pub const MAX_LIGHTS: usize = 4;
+
+// Radio angular mínimo de una luz (aproximadamente el del sol visto desde la
+// Tierra), para luces puntuales o direccionales sin tamaño
+const MIN_ANGULAR_RADIUS: f32 = 0.005;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightSource {
+    // Rayos paralelos; `direction` apunta hacia la luz
+    Directional { direction: Vec3 },
+    // Emite desde `position`; `falloff` es la distancia a la que la intensidad
+    // cae a la mitad (0 = sin atenuación)
+    Point { position: Vec3, falloff: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub source: LightSource,
+    // Color lineal; se multiplica por `intensity`
+    pub color: HdrColor,
+    pub intensity: f32,
+    // Radio del emisor (el del sol si la luz lo sigue), para las penumbras;
+    // 0 = una luz puntual
+    pub radius: f32,
+}
+
+impl Light {
+    pub fn directional(direction: Vec3, color: HdrColor, intensity: f32) -> Self {
+        Light {
+            source: LightSource::Directional { direction: direction.normalize() },
+            color,
+            intensity,
+            radius: 0.0,
+        }
+    }
+
+    pub fn point(position: Vec3, color: HdrColor, intensity: f32, falloff: f32) -> Self {
+        Light {
+            source: LightSource::Point { position, falloff },
+            color,
+            intensity,
+            radius: 0.0,
+        }
+    }
+
+    pub fn with_radius(self, radius: f32) -> Self {
+        Light { radius, ..self }
+    }
+
+    // Dirección unitaria desde `point` hacia la luz
+    pub fn direction_from(&self, point: Vec3) -> Vec3 {
+        match self.source {
+            LightSource::Directional { direction } => direction,
+            LightSource::Point { position, .. } => {
+                let offset = position - point;
+                let distance = offset.magnitude();
+                if distance > 1e-6 { offset / distance } else { Vec3::zeros() }
+            }
+        }
+    }
+
+    // Distancia desde `point` hasta la luz (infinita si es direccional)
+    pub fn distance_from(&self, point: Vec3) -> f32 {
+        match self.source {
+            LightSource::Directional { .. } => f32::INFINITY,
+            LightSource::Point { position, .. } => (position - point).magnitude(),
+        }
+    }
+
+    // Radio angular del emisor visto desde `point`, en radianes; nunca menor
+    // que MIN_ANGULAR_RADIUS para que las sombras tengan algo de penumbra
+    pub fn angular_radius_from(&self, point: Vec3) -> f32 {
+        let distance = self.distance_from(point);
+        if distance <= self.radius {
+            return std::f32::consts::FRAC_PI_2;
+        }
+        (self.radius / distance).asin().max(MIN_ANGULAR_RADIUS)
+    }
+
+    // Color e intensidad que llegan a `point`, ya atenuados por la distancia
+    pub fn radiance_at(&self, point: Vec3) -> HdrColor {
+        let attenuation = match self.source {
+            LightSource::Directional { .. } => 1.0,
+            LightSource::Point { falloff, .. } if falloff <= 0.0 => 1.0,
+            LightSource::Point { position, falloff } => {
+                let ratio = (position - point).magnitude() / falloff;
+                1.0 / (1.0 + ratio * ratio)
+            }
+        };
+        self.color * (self.intensity * attenuation)
+    }
+
+    // La misma luz con su posición o dirección expresada desde `origin`
+    pub fn relative_to(&self, origin: Vec3) -> Light {
+        match self.source {
+            LightSource::Directional { .. } => *self,
+            LightSource::Point { position, falloff } => Light {
+                source: LightSource::Point { position: position - origin, falloff },
+                ..*self
+            },
+        }
+    }
+}
+
+impl Default for Light {
+    // Blanca de intensidad 1 desde arriba a la derecha, como la luz fija original
+    fn default() -> Self {
+        Light::directional(Vec3::new(1.0, 1.0, 1.0), HdrColor::new(1.0, 1.0, 1.0), 1.0)
+    }
+}
+
+// Conjunto de luces de un frame, de tamaño fijo para que `Uniforms` siga siendo `Copy`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lights {
+    lights: [Light; MAX_LIGHTS],
+    count: usize,
+}
+
+impl Lights {
+    pub fn new() -> Self {
+        Lights { lights: [Light::default(); MAX_LIGHTS], count: 0 }
+    }
+
+    pub fn single(light: Light) -> Self {
+        let mut lights = Lights::new();
+        lights.lights[0] = light;
+        lights.count = 1;
+        lights
+    }
+
+    pub fn push(&mut self, light: Light) -> Result<(), String> {
+        if self.count == MAX_LIGHTS {
+            return Err(format!("Se admiten como máximo {} luces", MAX_LIGHTS));
+        }
+        self.lights[self.count] = light;
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Light> {
+        self.lights[..self.count].iter()
+    }
+
+    // La primera luz es la principal: la usan las sombras y los efectos que
+    // necesitan una sola dirección
+    pub fn main(&self) -> Option<&Light> {
+        self.iter().next()
+    }
+
+    // Dirección hacia la luz principal (cero si no hay luces)
+    pub fn main_direction(&self, point: Vec3) -> Vec3 {
+        self.main().map_or(Vec3::zeros(), |light| light.direction_from(point))
+    }
+
+    // Las mismas luces con la principal atenuada por una sombra (0 = tapada)
+    pub fn shadowed(&self, visibility: f32) -> Lights {
+        let mut lights = *self;
+        if lights.count > 0 {
+            lights.lights[0].intensity *= visibility.clamp(0.0, 1.0);
+        }
+        lights
+    }
+
+    // Suma de Lambert: cuánta luz de cada color recibe una superficie con esa normal
+    pub fn diffuse(&self, point: Vec3, normal: Vec3) -> HdrColor {
+        self.diffuse_with(point, normal, |_| 1.0)
+    }
+
+    // Lambert con la fracción de cada luz que llega a `point` (sombras); solo
+    // se consulta para las luces que dan de frente
+    pub fn diffuse_with(&self, point: Vec3, normal: Vec3, visibility: impl Fn(&Light) -> f32) -> HdrColor {
+        self.iter().fold(HdrColor::black(), |sum, light| {
+            let facing = normal.dot(&light.direction_from(point)).max(0.0);
+            if facing == 0.0 {
+                return sum;
+            }
+            sum + light.radiance_at(point) * (facing * visibility(light))
+        })
+    }
+}
+
+// Las que excedan MAX_LIGHTS se descartan; la escena ya lo valida al cargarse
+impl FromIterator<Light> for Lights {
+    fn from_iter<I: IntoIterator<Item = Light>>(iter: I) -> Self {
+        let mut lights = Lights::new();
+        for light in iter {
+            if lights.push(light).is_err() {
+                break;
+            }
+        }
+        lights
+    }
+}
+
+impl Default for Lights {
+    fn default() -> Self {
+        Lights::single(Light::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_lights_face_the_surface_and_fade_with_distance() {
+        let sun = Light::point(Vec3::zeros(), HdrColor::new(1.0, 0.5, 0.25), 2.0, 10.0);
+        let lights = Lights::single(sun);
+        let point = Vec3::new(10.0, 0.0, 0.0);
+
+        assert_eq!(sun.direction_from(point), Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(sun.radiance_at(point), HdrColor::new(1.0, 0.5, 0.25));
+
+        // El lado que mira al sol recibe toda la luz; el opuesto nada
+        assert_eq!(lights.diffuse(point, Vec3::new(-1.0, 0.0, 0.0)).r, 1.0);
+        assert_eq!(lights.diffuse(point, Vec3::new(1.0, 0.0, 0.0)), HdrColor::black());
+
+        let far = sun.radiance_at(Vec3::new(30.0, 0.0, 0.0));
+        assert!((far.r - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lights_are_capped_and_sum() {
+        let mut lights = Lights::new();
+        assert_eq!(lights.main_direction(Vec3::zeros()), Vec3::zeros());
+
+        for _ in 0..MAX_LIGHTS {
+            lights.push(Light::default()).unwrap();
+        }
+        assert!(lights.push(Light::default()).is_err());
+
+        let up = Light::default().direction_from(Vec3::zeros());
+        let total = lights.diffuse(Vec3::zeros(), up);
+        assert!((total.g - MAX_LIGHTS as f32).abs() < 1e-5);
+    }
+}
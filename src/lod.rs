@@ -0,0 +1,375 @@
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::obj_loader::Model;
+use crate::primitives::{create_icosphere, create_torus, create_uv_sphere};
+use crate::shaders::Uniforms;
+
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Largo (en píxeles) que se busca para las aristas en pantalla: un nivel
+// alcanza mientras sus aristas proyectadas no pasen de este tamaño
+const TARGET_EDGE_PIXELS: f32 = 8.0;
+
+// Margen relativo alrededor de cada umbral: el nivel solo cambia cuando el
+// radio proyectado lo cruza con holgura, así un cuerpo justo en el borde no parpadea
+const HYSTERESIS: f32 = 0.15;
+
+// Teselado mínimo al reducir esferas y toros a la mitad en cada nivel
+const MIN_SEGMENTS: u32 = 8;
+const MIN_RINGS: u32 = 4;
+
+// ============================================
+// CADENAS DE NIVELES DE DETALLE
+// ============================================
+
+pub struct LodLevel {
+    pub model: Arc<Model>,
+    // Radio proyectado (en píxeles) hasta el que se usa este nivel
+    pub max_radius: f32,
+}
+
+// Niveles ordenados del más grueso al más fino; el último no tiene límite
+pub struct LodChain {
+    levels: Vec<LodLevel>,
+    bounding_radius: f32,
+}
+
+impl LodChain {
+    // Los umbrales salen del largo medio de las aristas de cada malla
+    // relativo a su radio, así sirve para cualquier modelo centrado en el origen
+    pub fn new(models: Vec<Model>) -> Self {
+        assert!(!models.is_empty(), "una cadena de LOD necesita al menos un nivel");
+
+        let mut models = models;
+        models.sort_by_key(|model| model.triangle_count());
+
+        let bounding_radius = models
+            .iter()
+            .flat_map(|model| model.vertices.iter())
+            .map(|vertex| vertex.position.magnitude())
+            .fold(0.0, f32::max);
+
+        let count = models.len();
+        let mut previous = 0.0;
+        let levels = models
+            .into_iter()
+            .enumerate()
+            .map(|(i, model)| {
+                let max_radius = if i + 1 == count {
+                    f32::INFINITY
+                } else {
+                    let relative_edge = mean_edge_length(&model) / bounding_radius.max(f32::EPSILON);
+                    (TARGET_EDGE_PIXELS / relative_edge.max(f32::EPSILON)).max(previous)
+                };
+                previous = max_radius;
+
+                LodLevel { model: Arc::new(model), max_radius }
+            })
+            .collect();
+
+        LodChain { levels, bounding_radius }
+    }
+
+    // Un solo nivel, para mallas cargadas de archivo
+    pub fn single(model: Model) -> Self {
+        LodChain::new(vec![model])
+    }
+
+    // Esferas UV con la mitad de segmentos y anillos en cada nivel
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let mut models = vec![create_uv_sphere(radius, segments, rings)];
+        let (mut segments, mut rings) = (segments / 2, rings / 2);
+        while segments >= MIN_SEGMENTS && rings >= MIN_RINGS {
+            models.push(create_uv_sphere(radius, segments, rings));
+            segments /= 2;
+            rings /= 2;
+        }
+        LodChain::new(models)
+    }
+
+    // Un nivel por cada subdivisión, desde el icosaedro
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        LodChain::new((0..=subdivisions).map(|n| create_icosphere(radius, n)).collect())
+    }
+
+    pub fn torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> Self {
+        let mut models = vec![create_torus(major_radius, minor_radius, segments, sides)];
+        let (mut segments, mut sides) = (segments / 2, sides / 2);
+        while segments >= MIN_SEGMENTS && sides >= MIN_RINGS {
+            models.push(create_torus(major_radius, minor_radius, segments, sides));
+            segments /= 2;
+            sides /= 2;
+        }
+        LodChain::new(models)
+    }
+
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    // Radio de la esfera que contiene a todos los niveles, en espacio de modelo
+    pub fn bounding_radius(&self) -> f32 {
+        self.bounding_radius
+    }
+
+    pub fn finest(&self) -> &Model {
+        &self.levels[self.levels.len() - 1].model
+    }
+
+    pub fn level(&self, index: usize) -> &Model {
+        &self.levels[index].model
+    }
+
+    // Nivel para un radio proyectado. Con `previous` el nivel anterior se
+    // conserva mientras el radio no salga de su rango ampliado por la histéresis
+    pub fn select(&self, radius: f32, previous: Option<usize>) -> usize {
+        let target = self
+            .levels
+            .iter()
+            .position(|level| radius <= level.max_radius)
+            .unwrap_or(self.levels.len() - 1);
+
+        match previous {
+            Some(level) if level < self.levels.len() => {
+                let upper = self.levels[level].max_radius * (1.0 + HYSTERESIS);
+                let lower = if level == 0 { 0.0 } else { self.levels[level - 1].max_radius * (1.0 - HYSTERESIS) };
+
+                if radius >= lower && radius <= upper {
+                    level
+                } else {
+                    target
+                }
+            }
+            _ => target,
+        }
+    }
+}
+
+fn mean_edge_length(model: &Model) -> f32 {
+    let mut total = 0.0;
+    for [a, b, c] in model.triangles() {
+        total += (b.position - a.position).magnitude()
+            + (c.position - b.position).magnitude()
+            + (a.position - c.position).magnitude();
+    }
+    total / (3 * model.triangle_count()).max(1) as f32
+}
+
+// Centro en pantalla y radio en píxeles de una esfera en coordenadas de mundo,
+// usando la proyección de la cámara. None si queda detrás de la cámara; con
+// la cámara adentro el radio es infinito
+pub fn project_sphere(center: Vec3, radius: f32, uniforms: &Uniforms) -> Option<(Vec2, f32)> {
+    let view_center = uniforms.view_matrix * Vec4::new(center.x, center.y, center.z, 1.0);
+    let distance = view_center.xyz().magnitude();
+
+    let clip = uniforms.projection_matrix * view_center;
+    if distance <= radius {
+        return Some((Vec2::new(f32::NAN, f32::NAN), f32::INFINITY));
+    }
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let screen = uniforms.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+
+    // Radio angular exacto de la esfera, escalado por la proyección y el viewport
+    let tangent = radius / (distance * distance - radius * radius).sqrt();
+    let pixels = tangent * uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)].abs();
+
+    Some((Vec2::new(screen.x, screen.y), pixels))
+}
+
+// ============================================
+// SELECCIÓN ENTRE FRAMES Y OVERLAY DE DEPURACIÓN
+// ============================================
+
+struct LodMarker {
+    center: Vec2,
+    radius: f32,
+    level: usize,
+    level_count: usize,
+}
+
+// Recuerda el nivel de cada cuerpo entre frames (para la histéresis) y, con
+// el overlay activo, dónde se dibujó cada uno
+#[derive(Default)]
+pub struct LodState {
+    pub show_overlay: bool,
+    levels: HashMap<String, usize>,
+    markers: Vec<LodMarker>,
+}
+
+impl LodState {
+    pub fn new() -> Self {
+        LodState::default()
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.markers.clear();
+    }
+
+    pub fn level_of(&self, key: &str) -> Option<usize> {
+        self.levels.get(key).copied()
+    }
+
+    // Elige el nivel de `chain` para un cuerpo en `center` escalado por
+    // `scale`; `key` identifica al cuerpo entre frames
+    pub fn choose<'a>(
+        &mut self,
+        key: &str,
+        chain: &'a LodChain,
+        center: Vec3,
+        scale: f32,
+        uniforms: &Uniforms,
+    ) -> &'a Model {
+        let Some((screen, radius)) = project_sphere(center, chain.bounding_radius * scale, uniforms) else {
+            // Detrás de la cámara: se recorta entero, da igual el nivel
+            return chain.level(self.level_of(key).unwrap_or(0));
+        };
+
+        let level = chain.select(radius, self.level_of(key));
+        self.levels.insert(key.to_string(), level);
+
+        if self.show_overlay && radius.is_finite() {
+            self.markers.push(LodMarker { center: screen, radius, level, level_count: chain.levels.len() });
+        }
+
+        chain.level(level)
+    }
+
+    // Contorno de cada cuerpo con el color y el número de su nivel
+    // (verde el más fino, rojo el más grueso)
+    pub fn draw_overlay(&self, framebuffer: &mut Framebuffer) {
+        for marker in &self.markers {
+            let fineness = if marker.level_count > 1 {
+                marker.level as f32 / (marker.level_count - 1) as f32
+            } else {
+                1.0
+            };
+            let color = Color::from_float(1.0 - fineness, fineness, 0.2).to_hex();
+
+            let radius = marker.radius.max(3.0);
+            draw_circle(framebuffer, marker.center, radius, color);
+
+            // Número arriba a la derecha, sobre la diagonal del contorno
+            let label = marker.center + Vec2::new(radius, -radius) * std::f32::consts::FRAC_1_SQRT_2;
+            draw_digit(framebuffer, label.x as i32 + 2, label.y as i32 - 10, marker.level as u32 % 10, color);
+        }
+    }
+}
+
+fn put_pixel(framebuffer: &mut Framebuffer, x: i32, y: i32, color: u32) {
+    if x >= 0 && y >= 0 && (x as usize) < framebuffer.width && (y as usize) < framebuffer.height {
+        framebuffer.buffer[y as usize * framebuffer.width + x as usize] = color;
+    }
+}
+
+fn draw_circle(framebuffer: &mut Framebuffer, center: Vec2, radius: f32, color: u32) {
+    // Suficientes pasos para que el contorno no tenga huecos; acotado para
+    // cuerpos que cubren toda la pantalla
+    let steps = ((radius * 2.0 * std::f32::consts::PI) as usize).clamp(16, 4096);
+    for i in 0..steps {
+        let angle = i as f32 / steps as f32 * 2.0 * std::f32::consts::PI;
+        let x = center.x + radius * angle.cos();
+        let y = center.y + radius * angle.sin();
+        put_pixel(framebuffer, x as i32, y as i32, color);
+    }
+}
+
+// Dígitos de 3x5 píxeles, una fila por byte (bit 2 = columna izquierda)
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+// Dibuja un dígito al doble de tamaño con la esquina superior izquierda en (x, y)
+fn draw_digit(framebuffer: &mut Framebuffer, x: i32, y: i32, digit: u32, color: u32) {
+    for (row, bits) in DIGITS[digit as usize].iter().enumerate() {
+        for column in 0..3 {
+            if bits & (0b100 >> column) == 0 {
+                continue;
+            }
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                put_pixel(framebuffer, x + column * 2 + dx, y + row as i32 * 2 + dy, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::default_camera;
+    use crate::shaders::create_viewport_matrix;
+    use nalgebra_glm::Mat4;
+
+    fn uniforms(eye: Vec3) -> Uniforms {
+        let mut camera = default_camera(false);
+        camera.eye = eye;
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: camera.get_view_matrix(),
+            projection_matrix: camera.get_projection_matrix(800.0 / 600.0),
+            viewport_matrix: create_viewport_matrix(800.0, 600.0),
+            time: 0.0,
+            lights: crate::light::Lights::default(),
+            camera_position: eye,
+            occluders: crate::occlusion::Occluders::default(),
+            body_radius: 1.0,
+        }
+    }
+
+    #[test]
+    fn projected_radius_matches_the_rendered_silhouette() {
+        // Esfera de radio 1 a distancia 5 con fov vertical de 45°:
+        // tan(asin(1/5)) / tan(22.5°) * 300 px
+        let (center, radius) = project_sphere(Vec3::zeros(), 1.0, &uniforms(Vec3::new(0.0, 0.0, 5.0))).unwrap();
+        let expected = (1.0f32 / 24.0f32.sqrt()) / (std::f32::consts::PI / 8.0).tan() * 300.0;
+
+        assert!((radius - expected).abs() < 1e-3, "{} != {}", radius, expected);
+        assert!((center - Vec2::new(400.0, 300.0)).magnitude() < 1e-3);
+
+        assert!(project_sphere(Vec3::new(0.0, 0.0, 10.0), 1.0, &uniforms(Vec3::new(0.0, 0.0, 5.0))).is_none());
+    }
+
+    #[test]
+    fn farther_bodies_use_coarser_levels() {
+        let chain = LodChain::icosphere(1.0, 4);
+        assert_eq!(chain.levels().len(), 5);
+
+        let mut last = usize::MAX;
+        for distance in [3.0, 10.0, 30.0, 100.0, 400.0] {
+            let (_, radius) = project_sphere(Vec3::zeros(), 1.0, &uniforms(Vec3::new(0.0, 0.0, distance))).unwrap();
+            let level = chain.select(radius, None);
+            assert!(level <= last, "a {} se eligió el nivel {} después del {}", distance, level, last);
+            last = level;
+        }
+        assert_eq!(chain.select(f32::INFINITY, None), 4);
+        assert_eq!(chain.select(0.5, None), 0);
+    }
+
+    #[test]
+    fn hysteresis_keeps_the_level_near_a_threshold() {
+        let chain = LodChain::icosphere(1.0, 3);
+        let threshold = chain.levels()[1].max_radius;
+
+        // Justo por encima del umbral sin historia se sube de nivel...
+        assert_eq!(chain.select(threshold * 1.05, None), 2);
+        // ...pero si el cuerpo venía del nivel 1 se queda ahí
+        assert_eq!(chain.select(threshold * 1.05, Some(1)), 1);
+        assert_eq!(chain.select(threshold * 0.95, Some(2)), 2);
+        // Cruzando el margen sí cambia
+        assert_eq!(chain.select(threshold * 1.3, Some(1)), 2);
+        assert_eq!(chain.select(threshold * 0.7, Some(2)), 1);
+    }
+}
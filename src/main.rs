@@ -1,70 +1,93 @@
 mod color;
 mod framebuffer;
 mod triangle;
+mod clipping;
 mod obj_loader;
 mod vertex;
 mod fragment;
 mod shaders;
 mod camera;
-mod ring;
+mod primitives;
+mod lod;
+mod planet;
+mod renderer;
+mod scene;
+mod shader_registry;
+mod texture;
+mod material;
+mod headless;
+mod tonemap;
+mod postprocess;
+mod light;
+mod shadow;
+mod occlusion;
+mod atmosphere;
+mod glow;
 
-use crate::color::Color;
 use crate::framebuffer::{Framebuffer, SCREEN_WIDTH, SCREEN_HEIGHT};
-use crate::triangle::Triangle;
-use crate::obj_loader::Model;
-use crate::vertex::Vertex;
-use crate::shaders::{vertex_shader, fragment_shader, create_model_matrix, create_viewport_matrix, Uniforms};
-use crate::camera::Camera;
-use crate::ring::create_ring_vertices;
+use crate::camera::default_camera;
+use crate::renderer::{render_planet_view, render_system_view, SceneAssets};
+use crate::scene::Scene;
+use crate::shader_registry::ShaderRegistry;
+use crate::headless::HeadlessOptions;
+use crate::lod::LodState;
 
 use minifb::{Key, Window, WindowOptions};
-use nalgebra_glm::{Vec3, Mat4};
+use nalgebra_glm::Vec3;
 use std::time::Instant;
 
-fn render(
-    framebuffer: &mut Framebuffer,
-    uniforms: &Uniforms,
-    vertex_array: &[Vertex],
-    shader_type: &str,
-) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", headless::USAGE);
+        return;
     }
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push(Triangle::new_from_vertices(
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ));
-        }
+    if args.iter().any(|arg| arg == "--list-shaders") {
+        let registry = ShaderRegistry::with_builtin_shaders();
+        println!("Shaders de fragmentos: {}", registry.fragment_names().join(", "));
+        println!("Shaders de vértices: {}", registry.vertex_names().join(", "));
+        return;
     }
 
-    let mut all_fragments = Vec::new();
-    for triangle in &triangles {
-        let fragments = triangle.draw(framebuffer);
-        all_fragments.extend(fragments);
+    if args.iter().any(|arg| arg == "--headless") {
+        run_headless(&args);
+        return;
     }
 
-    for fragment in all_fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = fragment_shader(&fragment, uniforms, shader_type);
-            framebuffer.set_current_color(shaded_color);
-            framebuffer.point(x, y, fragment.depth);
-        }
+    run_window(&args);
+}
+
+fn run_headless(args: &[String]) {
+    let options = HeadlessOptions::from_args(args).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, headless::USAGE);
+        std::process::exit(2);
+    });
+
+    let mut assets = SceneAssets::new();
+    let mut scene = load_scene(options.scene_path.as_deref(), &mut assets.shaders);
+
+    if let Err(e) = headless::run(&options, &assets, &mut scene) {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
 
-fn main() {
+fn load_scene(path: Option<&str>, shaders: &mut ShaderRegistry) -> Scene {
+    let scene = Scene::load_or_default(path, shaders).unwrap_or_else(|e| {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    });
+    for warning in &scene.warnings {
+        eprintln!("⚠️  {}", warning);
+    }
+    scene
+}
+
+fn run_window(args: &[String]) {
     let mut window = Window::new(
-        "Sistema Solar - Proyecto 2 [1-6: Planetas | WASD: Cámara | R: Reset | ESC: Salir]",
+        "Sistema Solar - Proyecto 2 [1-6: Planetas | WASD: Cámara | R: Reset | L: LOD | ESC: Salir]",
         SCREEN_WIDTH,
         SCREEN_HEIGHT,
         WindowOptions::default(),
@@ -76,27 +99,26 @@ fn main() {
     window.set_target_fps(60);
 
     let mut framebuffer = Framebuffer::new(SCREEN_WIDTH, SCREEN_HEIGHT);
-    framebuffer.set_background_color(Color::new(10, 5, 20));
 
-    // Cargar modelo de esfera para planetas
-    let mut model = Model::load_from_file("sphere.obj")
-        .expect("No se pudo cargar sphere.obj");
-    model.normalize_and_center(1.5);
+    let mut assets = SceneAssets::new();
 
-    // Crear modelo para la luna (más pequeño)
-    let mut moon_model = Model::load_from_file("sphere.obj")
-        .expect("No se pudo cargar sphere.obj para la luna");
-    moon_model.normalize_and_center(0.4);
-
-    // Crear anillos de Saturno
-    let ring_vertices = create_ring_vertices(1.2, 1.8, 100);
+    let scene_path = args
+        .iter()
+        .position(|arg| arg == "--scene")
+        .and_then(|i| args.get(i + 1))
+        .map(|path| path.as_str());
+    let mut scene = load_scene(scene_path, &mut assets.shaders);
 
     println!("🌍 Sistema Solar - Proyecto 2");
     println!("================================");
-    println!("Modelo cargado: sphere.obj");
-    println!("  Vértices planeta: {}", model.vertices.len());
-    println!("  Vértices luna: {}", moon_model.vertices.len());
-    println!("  Vértices anillos: {}", ring_vertices.len());
+    let sphere_levels = assets.planet_lod.levels();
+    println!(
+        "Esfera generada: {} niveles de detalle, de {} a {} triángulos",
+        sphere_levels.len(),
+        sphere_levels[0].model.triangle_count(),
+        assets.planet_lod.finest().triangle_count()
+    );
+    println!("Escena: {} ({} cuerpos principales)", scene_path.unwrap_or("por defecto"), scene.bodies.len());
     println!("\n🎮 CONTROLES:");
     println!("  [1] ☀️  Sol");
     println!("  [2] 🔴 Marte");
@@ -104,29 +126,35 @@ fn main() {
     println!("  [4] 🟠 Júpiter");
     println!("  [5] 🪐 Saturno (con Anillos 💍)");
     println!("  [6] 🔵 Neptuno");
+    println!("  [7] 🌌 Sistema Solar completo");
     println!("  [0] 🧪 Test Shader");
     println!("\n  W/S o ↑/↓: Orbitar verticalmente");
     println!("  A/D o ←/→: Orbitar horizontalmente");
     println!("  Q/E: Zoom");
     println!("  R: Resetear cámara");
+    println!("  L: Overlay de niveles de detalle");
+    println!("  T: Cambiar tone mapping | Z/X: Exposición");
+    println!("  B: Bloom");
+    println!("  O: Sombras entre cuerpos (none / analytic / map)");
     println!("  ESC: Salir");
     println!("================================\n");
 
-    let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 4.5),
-        Vec3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-    );
+    let mut camera = default_camera(false);
+    let mut lod = LodState::new();
 
     let mut rotation = Vec3::new(0.0, 0.0, 0.0);
     let mut current_shader = "test";
     let mut current_planet = "Test";
     let start_time = Instant::now();
+    let mut last_frame = start_time;
 
     println!("Planeta actual: {} (Shader: {})", current_planet, current_shader);
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let time = start_time.elapsed().as_secs_f32();
+        let now = Instant::now();
+        let delta_time = now.duration_since(last_frame).as_secs_f32();
+        last_frame = now;
 
         // SELECCIÓN DE PLANETAS
         if window.is_key_pressed(Key::Key1, minifb::KeyRepeat::No) {
@@ -159,6 +187,12 @@ fn main() {
             current_planet = "Neptuno 🔵";
             println!("\n❄️  Cambiado a: {} (Shader: {})", current_planet, current_shader);
         }
+        if window.is_key_pressed(Key::Key7, minifb::KeyRepeat::No) {
+            current_shader = "system";
+            current_planet = "Sistema Solar 🌌";
+            camera = default_camera(true);
+            println!("\n🌌 Cambiado a: {}", current_planet);
+        }
         if window.is_key_pressed(Key::Key0, minifb::KeyRepeat::No) {
             current_shader = "test";
             current_planet = "Test 🧪";
@@ -187,79 +221,55 @@ fn main() {
 
         // Reset cámara
         if window.is_key_pressed(Key::R, minifb::KeyRepeat::No) {
-            camera = Camera::new(
-                Vec3::new(0.0, 0.0, 4.5),
-                Vec3::new(0.0, 0.0, 0.0),
-                Vec3::new(0.0, 1.0, 0.0),
-            );
+            camera = default_camera(current_shader == "system");
             rotation = Vec3::new(0.0, 0.0, 0.0);
             println!("📷 Cámara reseteada");
         }
 
-        framebuffer.clear();
-
-        // RENDERIZAR PLANETA PRINCIPAL
-        let model_matrix = create_model_matrix(Vec3::new(0.0, 0.0, 0.0), 1.0, rotation);
-        let view_matrix = camera.get_view_matrix();
-        let projection_matrix = camera.get_projection_matrix(SCREEN_WIDTH as f32 / SCREEN_HEIGHT as f32);
-        let viewport_matrix = create_viewport_matrix(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        if window.is_key_pressed(Key::L, minifb::KeyRepeat::No) {
+            lod.show_overlay = !lod.show_overlay;
+            println!("🔍 Overlay de LOD: {}", if lod.show_overlay { "activado" } else { "desactivado" });
+        }
 
-        let uniforms = Uniforms {
-            model_matrix,
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            light_dir: Vec3::new(1.0, 1.0, 1.0),
-        };
+        // TONE MAPPING
+        if window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
+            scene.tone_mapping.operator = scene.tone_mapping.operator.next();
+            println!("🎞️  Tone mapping: {}", scene.tone_mapping.operator.name());
+        }
+        if window.is_key_pressed(Key::Z, minifb::KeyRepeat::Yes) {
+            scene.tone_mapping.exposure = (scene.tone_mapping.exposure / 1.1).max(0.05);
+            println!("🔆 Exposición: {:.2}", scene.tone_mapping.exposure);
+        }
+        if window.is_key_pressed(Key::X, minifb::KeyRepeat::Yes) {
+            scene.tone_mapping.exposure = (scene.tone_mapping.exposure * 1.1).min(20.0);
+            println!("🔆 Exposición: {:.2}", scene.tone_mapping.exposure);
+        }
 
-        render(&mut framebuffer, &uniforms, &model.vertices, current_shader);
+        if window.is_key_pressed(Key::B, minifb::KeyRepeat::No) {
+            let bloom = &mut scene.post_processing.bloom;
+            bloom.enabled = !bloom.enabled;
+            println!("✨ Bloom: {}", if bloom.enabled { "activado" } else { "desactivado" });
+        }
 
-        // RENDERIZAR LUNA SI ESTAMOS EN LA TIERRA
-        if current_shader == "rocky_earth" {
-            let moon_orbit_radius = 2.5;
-            let moon_orbit_speed = 0.5;
-            let moon_angle = time * moon_orbit_speed;
-            
-            let moon_position = Vec3::new(
-                moon_angle.cos() * moon_orbit_radius,
-                0.0,
-                moon_angle.sin() * moon_orbit_radius
-            );
-            
-            let moon_model_matrix = create_model_matrix(moon_position, 0.27, Vec3::new(0.0, time * 0.1, 0.0));
-            
-            let moon_uniforms = Uniforms {
-                model_matrix: moon_model_matrix,
-                view_matrix,
-                projection_matrix,
-                viewport_matrix,
-                time,
-                light_dir: Vec3::new(1.0, 1.0, 1.0),
-            };
-            
-            render(&mut framebuffer, &moon_uniforms, &moon_model.vertices, "moon");
+        if window.is_key_pressed(Key::O, minifb::KeyRepeat::No) {
+            scene.shadows = scene.shadows.next();
+            println!("🌑 Sombras: {}", scene.shadows.name());
         }
 
-        // RENDERIZAR ANILLOS SI ESTAMOS EN SATURNO
-        if current_shader == "gas_saturn" {
-            let ring_rotation = Vec3::new(0.4, rotation.y, 0.0);
-            let ring_model_matrix = create_model_matrix(Vec3::new(0.0, 0.0, 0.0), 1.0, ring_rotation);
-            
-            let ring_uniforms = Uniforms {
-                model_matrix: ring_model_matrix,
-                view_matrix,
-                projection_matrix,
-                viewport_matrix,
-                time,
-                light_dir: Vec3::new(1.0, 1.0, 1.0),
-            };
-            
-            render(&mut framebuffer, &ring_uniforms, &ring_vertices, "ring");
+        let result = if current_shader == "system" {
+            scene.update(delta_time);
+            render_system_view(&mut framebuffer, &assets, &mut lod, &scene, &camera, time)
+        } else {
+            render_planet_view(&mut framebuffer, &assets, &mut lod, &scene, &camera, current_shader, rotation, time)
+        };
+
+        if let Err(e) = result {
+            eprintln!("❌ {}", e);
+            break;
         }
 
         window
             .update_with_buffer(&framebuffer.buffer, SCREEN_WIDTH, SCREEN_HEIGHT)
             .unwrap();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use crate::color::{Color, HdrColor, Rgba};
+use crate::fragment::Fragment;
+use crate::shaders::{view_direction, Uniforms};
+use crate::shader_registry::FragmentShader;
+use crate::texture::{Sampler, Texture};
+
+use nalgebra_glm::{Vec2, Vec3};
+use std::path::Path;
+use std::sync::Arc;
+
+// Pseudo-shader: cada submalla se sombrea con el material de su `usemtl`
+pub const MATERIAL_SHADER: &str = "material";
+
+// Cuánto inclinan la normal las pendientes del mapa de relieve
+const BUMP_STRENGTH: f32 = 2.0;
+
+// Material de un archivo MTL con sus texturas ya cargadas
+#[derive(Clone)]
+pub struct Material {
+    pub name: String,
+    pub diffuse: Vec3,                      // Kd
+    pub specular: Vec3,                     // Ks
+    pub shininess: f32,                     // Ns
+    pub diffuse_map: Option<Arc<Texture>>,  // map_Kd
+    pub bump_map: Option<Arc<Texture>>,     // map_Bump (mapa de alturas)
+}
+
+impl Default for Material {
+    // Gris mate, el material por defecto de Blender
+    fn default() -> Self {
+        Material {
+            name: "default".to_string(),
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zeros(),
+            shininess: 1.0,
+            diffuse_map: None,
+            bump_map: None,
+        }
+    }
+}
+
+// Carga todos los materiales de un .mtl; las texturas son relativas al archivo
+pub fn load_mtl(path: &Path) -> Result<Vec<Material>, String> {
+    let (materials, _) = tobj::load_mtl(path)
+        .map_err(|e| format!("Error cargando materiales {}: {}", path.display(), e))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    materials
+        .into_iter()
+        .map(|material| {
+            let defaults = Material::default();
+            let texture = |spec: &Option<String>| -> Result<Option<Arc<Texture>>, String> {
+                match spec {
+                    Some(spec) => {
+                        let file = base_dir.join(texture_file(spec));
+                        let texture = Texture::load_from_file(&file.to_string_lossy())
+                            .map_err(|e| format!("Material '{}': {}", material.name, e))?;
+                        Ok(Some(Arc::new(texture)))
+                    }
+                    None => Ok(None),
+                }
+            };
+
+            Ok(Material {
+                diffuse: material.diffuse.map(Vec3::from).unwrap_or(defaults.diffuse),
+                specular: material.specular.map(Vec3::from).unwrap_or(defaults.specular),
+                shininess: material.shininess.unwrap_or(defaults.shininess).max(1.0),
+                diffuse_map: texture(&material.diffuse_texture)?,
+                bump_map: texture(&material.normal_texture)?,
+                name: material.name,
+            })
+        })
+        .collect()
+}
+
+// Blender puede anteponer opciones al archivo (`map_Bump -bm 1.0 relieve.png`)
+fn texture_file(spec: &str) -> &str {
+    if spec.starts_with('-') {
+        spec.split_whitespace().last().unwrap_or(spec)
+    } else {
+        spec
+    }
+}
+
+// ============================================
+// SHADER: MATERIAL (Kd/map_Kd difuso, Ks/Ns especular, relieve con map_Bump)
+// ============================================
+pub struct MaterialShader<'a> {
+    pub material: &'a Material,
+    pub sampler: Sampler,
+}
+
+impl<'a> MaterialShader<'a> {
+    pub fn new(material: &'a Material) -> Self {
+        MaterialShader { material, sampler: Sampler::default() }
+    }
+
+    // Inclina la normal según el gradiente del mapa de alturas, en la base
+    // tangente del fragmento
+    fn bumped_normal(&self, bump: &Texture, fragment: &Fragment, normal: Vec3) -> Vec3 {
+        let uv = fragment.tex_coords;
+        let du = Vec2::new(1.0 / bump.width as f32, 0.0);
+        let dv = Vec2::new(0.0, 1.0 / bump.height as f32);
+        let height = |uv: Vec2| luminance(self.sampler.sample(bump, uv));
+
+        let slope_u = height(uv + du) - height(uv - du);
+        let slope_v = height(uv + dv) - height(uv - dv);
+
+        let tangent = fragment.tangent.xyz();
+        let tangent = tangent - normal * normal.dot(&tangent);
+        if tangent.magnitude() < 1e-6 {
+            return normal;
+        }
+        let tangent = tangent.normalize();
+        let bitangent = normal.cross(&tangent) * fragment.tangent.w.signum();
+
+        (normal - (tangent * slope_u + bitangent * slope_v) * BUMP_STRENGTH).normalize()
+    }
+}
+
+impl FragmentShader for MaterialShader<'_> {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        let material = self.material;
+        let uv = fragment.tex_coords;
+
+        let mut normal = fragment.normal.normalize();
+        if let Some(bump) = &material.bump_map {
+            normal = self.bumped_normal(bump, fragment, normal);
+        }
+
+        // Kd ya es lineal; las texturas están en sRGB
+        let mut albedo = material.diffuse;
+        if let Some(texture) = &material.diffuse_map {
+            let texel = HdrColor::from(self.sampler.sample(texture, uv));
+            albedo = albedo.component_mul(&Vec3::new(texel.r, texel.g, texel.b));
+        }
+
+        let view_dir = view_direction(fragment, uniforms);
+        let point = fragment.world_position;
+
+        let ambient = 0.15;
+        let mut color = albedo * ambient;
+
+        for light in uniforms.lights.iter() {
+            let light_dir = light.direction_from(point);
+            let radiance = light.radiance_at(point) * uniforms.occluders.visibility(point, light);
+            let radiance = Vec3::new(radiance.r, radiance.g, radiance.b);
+
+            let diffuse = normal.dot(&light_dir).max(0.0);
+            color += albedo.component_mul(&radiance) * (diffuse * 0.85);
+
+            if diffuse > 0.0 {
+                let half_dir = (light_dir + view_dir).normalize();
+                let highlight = normal.dot(&half_dir).max(0.0).powf(material.shininess);
+                color += material.specular.component_mul(&radiance) * highlight;
+            }
+        }
+
+        Rgba::opaque(HdrColor::new(color.x, color.y, color.z))
+    }
+}
+
+fn luminance(color: Color) -> f32 {
+    (0.299 * color.r as f32 + 0.587 * color.g as f32 + 0.114 * color.b as f32) / 255.0
+}
@@ -1,94 +1,101 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::vertex::Vertex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::material::{load_mtl, Material};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
 
+// Cómo reaccionar ante datos inválidos en un OBJ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    // El primer problema aborta la carga indicando archivo:línea:columna
+    Strict,
+    // Los problemas se devuelven como advertencias: los números inválidos
+    // valen 0 y las caras con índices inválidos se descartan
+    Lenient,
+}
+
+// Tramo de `indices` que usa un mismo material (None: material por defecto)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubMesh {
+    pub material: Option<usize>,
+    pub indices: Range<usize>,
+}
+
+// Malla indexada: cada vértice único aparece una vez en `vertices` y cada
+// triángulo son tres índices consecutivos de `indices`
+#[derive(Clone)]
 pub struct Model {
     pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub materials: Vec<Material>,
+    pub submeshes: Vec<SubMesh>,
 }
 
 impl Model {
-    pub fn load_from_file(filename: &str) -> Result<Self, String> {
-        let file = File::open(filename)
-            .map_err(|e| format!("Error abriendo archivo: {}", e))?;
-        let reader = BufReader::new(file);
-
-        let mut temp_positions = Vec::new();
-        let mut temp_normals = Vec::new();
-        let mut temp_texcoords = Vec::new();
-        let mut vertices = Vec::new();
+    // Malla sin materiales: una sola submalla con todos los triángulos
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        assert_eq!(indices.len() % 3, 0, "la cantidad de índices debe ser múltiplo de 3");
+        debug_assert!(indices.iter().all(|&i| (i as usize) < vertices.len()), "índice fuera de rango");
+        let submeshes = vec![SubMesh { material: None, indices: 0..indices.len() }];
+        Model { vertices, indices, materials: Vec::new(), submeshes }
+    }
 
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Error leyendo línea: {}", e))?;
-            let line = line.trim();
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
 
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
+    // Vértices de cada triángulo, en orden
+    pub fn triangles(&self) -> impl Iterator<Item = [&Vertex; 3]> {
+        self.indices.chunks_exact(3).map(|t| {
+            [&self.vertices[t[0] as usize], &self.vertices[t[1] as usize], &self.vertices[t[2] as usize]]
+        })
+    }
 
-            let parts: Vec<&str> = line.split_whitespace().collect();
+    // Devuelve el modelo junto con las advertencias (solo en modo tolerante)
+    pub fn load_with_mode(filename: &str, mode: ParseMode) -> Result<(Self, Vec<String>), String> {
+        let contents = fs::read_to_string(filename)
+            .map_err(|e| format!("Error abriendo archivo {}: {}", filename, e))?;
 
-            match parts.get(0) {
-                Some(&"v") => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        temp_positions.push(Vec3::new(x, y, z));
-                    }
-                }
-                Some(&"vn") => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        temp_normals.push(Vec3::new(x, y, z));
-                    }
-                }
-                Some(&"vt") => {
-                    if parts.len() >= 3 {
-                        let u: f32 = parts[1].parse().unwrap_or(0.0);
-                        let v: f32 = parts[2].parse().unwrap_or(0.0);
-                        temp_texcoords.push(Vec2::new(u, v));
-                    }
-                }
-                Some(&"f") => {
-                    let mut face_vertices = Vec::new();
-
-                    for i in 1..parts.len() {
-                        let indices: Vec<&str> = parts[i].split('/').collect();
-
-                        let pos_idx: usize = indices[0].parse::<usize>().unwrap_or(1) - 1;
-                        let tex_idx: usize = if indices.len() > 1 && !indices[1].is_empty() {
-                            indices[1].parse::<usize>().unwrap_or(1) - 1
-                        } else {
-                            0
-                        };
-                        let norm_idx: usize = if indices.len() > 2 {
-                            indices[2].parse::<usize>().unwrap_or(1) - 1
-                        } else {
-                            0
-                        };
-
-                        let position = temp_positions.get(pos_idx).cloned().unwrap_or(Vec3::zeros());
-                        let normal = temp_normals.get(norm_idx).cloned().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                        let tex_coords = temp_texcoords.get(tex_idx).cloned().unwrap_or(Vec2::zeros());
-
-                        face_vertices.push(Vertex::new(position, normal, tex_coords));
-                    }
+        Model::parse(&contents, filename, mode)
+    }
 
-                    // Triangulación en abanico
-                    for i in 1..(face_vertices.len() - 1) {
-                        vertices.push(face_vertices[0].clone());
-                        vertices.push(face_vertices[i].clone());
-                        vertices.push(face_vertices[i + 1].clone());
-                    }
-                }
-                _ => {}
-            }
+    // `filename` se usa en los mensajes de error y para ubicar los `mtllib`
+    pub fn parse(contents: &str, filename: &str, mode: ParseMode) -> Result<(Self, Vec<String>), String> {
+        let mut parser = ObjParser {
+            filename,
+            base_dir: Path::new(filename).parent().unwrap_or(Path::new(".")),
+            mode,
+            warnings: Vec::new(),
+            positions: Vec::new(),
+            normals: Vec::new(),
+            texcoords: Vec::new(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            unique: HashMap::new(),
+            materials: Vec::new(),
+            material_names: HashMap::new(),
+            current_material: None,
+            submeshes: Vec::new(),
+            missing_normals: Vec::new(),
+        };
+
+        for (line_index, line) in contents.lines().enumerate() {
+            parser.parse_line(line_index + 1, line)?;
         }
 
-        Ok(Model { vertices })
+        let mut model = Model::new(parser.vertices, parser.indices);
+        model.materials = parser.materials;
+        model.submeshes = parser.submeshes;
+
+        // Solo se calculan las normales que el archivo no trae
+        if parser.missing_normals.contains(&true) {
+            model.smooth_normals(Some(&parser.missing_normals));
+        }
+        model.generate_tangents();
+
+        Ok((model, parser.warnings))
     }
 
     pub fn get_bounds(&self) -> (Vec3, Vec3) {
@@ -128,4 +135,610 @@ impl Model {
             vertex.position = (vertex.position - center) * scale_factor;
         }
     }
-}
\ No newline at end of file
+}
+
+// ============================================
+// POST-PROCESO: NORMALES Y TANGENTES
+// ============================================
+
+impl Model {
+    // Normales suaves: suma de las normales de las caras vecinas pesadas por
+    // el ángulo que cada cara forma en el vértice. Los vértices con la misma
+    // posición (costuras de UV) reciben la misma normal
+    pub fn generate_smooth_normals(&mut self) {
+        self.smooth_normals(None);
+    }
+
+    // Con `only`, solo se reemplazan las normales de los vértices marcados
+    fn smooth_normals(&mut self, only: Option<&[bool]>) {
+        let mut accumulated: HashMap<[u32; 3], Vec3> = HashMap::new();
+
+        for triangle in self.indices.chunks_exact(3) {
+            let p = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            let Some(face_normal) = face_normal(&p) else {
+                continue;
+            };
+
+            for i in 0..3 {
+                let angle = angle_between(&(p[(i + 1) % 3] - p[i]), &(p[(i + 2) % 3] - p[i]));
+                *accumulated.entry(position_key(&p[i])).or_insert(Vec3::zeros()) += face_normal * angle;
+            }
+        }
+
+        for (i, vertex) in self.vertices.iter_mut().enumerate() {
+            if only.is_some_and(|mask| !mask[i]) {
+                continue;
+            }
+            if let Some(sum) = accumulated.get(&position_key(&vertex.position)) {
+                if sum.magnitude() > 0.0 {
+                    vertex.normal = sum.normalize();
+                }
+            }
+        }
+    }
+
+    // Normales planas: cada triángulo usa su normal geométrica, así que los
+    // vértices se duplican donde se juntan caras con distinta orientación
+    pub fn generate_flat_normals(&mut self) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::with_capacity(self.indices.len());
+        let mut unique: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+
+        for triangle in self.indices.chunks_exact(3) {
+            let p = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            let normal = face_normal(&p);
+
+            for &index in triangle {
+                let normal = normal.unwrap_or(self.vertices[index as usize].normal);
+                let new_index = *unique.entry((index, position_key(&normal))).or_insert_with(|| {
+                    let mut vertex = self.vertices[index as usize].clone();
+                    vertex.normal = normal;
+                    vertices.push(vertex);
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(new_index);
+            }
+        }
+
+        // El orden de los índices no cambia, así que las submallas siguen valiendo
+        self.vertices = vertices;
+        self.indices = indices;
+    }
+
+    // Tangentes a partir de las coordenadas de textura, ortogonalizadas
+    // contra la normal. w = -1 cuando las UV están espejadas y la bitangente
+    // es -cross(normal, tangente)
+    pub fn generate_tangents(&mut self) {
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zeros(); self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let (v0, v1, v2) = (&self.vertices[a], &self.vertices[b], &self.vertices[c]);
+
+            let edge1 = v1.position - v0.position;
+            let edge2 = v2.position - v0.position;
+            let duv1 = v1.tex_coords - v0.tex_coords;
+            let duv2 = v2.tex_coords - v0.tex_coords;
+
+            let det = duv1.x * duv2.y - duv2.x * duv1.y;
+            if det.abs() < 1e-12 {
+                continue;
+            }
+
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) / det;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) / det;
+            for index in [a, b, c] {
+                tangents[index] += tangent;
+                bitangents[index] += bitangent;
+            }
+        }
+
+        for (i, vertex) in self.vertices.iter_mut().enumerate() {
+            let normal = vertex.normal;
+            let projected = tangents[i] - normal * normal.dot(&tangents[i]);
+            let tangent = if projected.magnitude() > 1e-6 {
+                projected.normalize()
+            } else {
+                any_perpendicular(&normal)
+            };
+            let handedness = if normal.cross(&tangent).dot(&bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+
+            vertex.tangent = Vec4::new(tangent.x, tangent.y, tangent.z, handedness);
+        }
+    }
+}
+
+fn face_normal(p: &[Vec3; 3]) -> Option<Vec3> {
+    let normal = (p[1] - p[0]).cross(&(p[2] - p[0]));
+    if normal.magnitude() > 0.0 {
+        Some(normal.normalize())
+    } else {
+        None
+    }
+}
+
+fn angle_between(a: &Vec3, b: &Vec3) -> f32 {
+    let lengths = a.magnitude() * b.magnitude();
+    if lengths == 0.0 {
+        return 0.0;
+    }
+    (a.dot(b) / lengths).clamp(-1.0, 1.0).acos()
+}
+
+// Clave exacta para agrupar vectores iguales (sumar 0.0 convierte -0.0 en 0.0)
+fn position_key(v: &Vec3) -> [u32; 3] {
+    [(v.x + 0.0).to_bits(), (v.y + 0.0).to_bits(), (v.z + 0.0).to_bits()]
+}
+
+// Tangente arbitraria para vértices sin UV utilizables
+fn any_perpendicular(normal: &Vec3) -> Vec3 {
+    let reference = if normal.y.abs() < 0.999 { Vec3::y() } else { Vec3::x() };
+    let tangent = reference.cross(normal);
+    if tangent.magnitude() > 0.0 {
+        tangent.normalize()
+    } else {
+        Vec3::x()
+    }
+}
+
+// ============================================
+// PARSER OBJ
+// ============================================
+
+struct ObjParser<'a> {
+    filename: &'a str,
+    base_dir: &'a Path,
+    mode: ParseMode,
+    warnings: Vec<String>,
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    texcoords: Vec<Vec2>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    // Combinación posición/uv/normal ya emitida -> índice en `vertices`
+    unique: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+    materials: Vec<Material>,
+    material_names: HashMap<String, usize>,
+    current_material: Option<usize>,
+    submeshes: Vec<SubMesh>,
+    // Vértices cuya cara no indicaba `vn`
+    missing_normals: Vec<bool>,
+}
+
+impl ObjParser<'_> {
+    fn parse_line(&mut self, line_number: usize, line: &str) -> Result<(), String> {
+        let tokens = tokenize(line);
+        let Some(&(_, keyword)) = tokens.first() else {
+            return Ok(());
+        };
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = self.numbers(line_number, line, &tokens, 3)?;
+                self.positions.push(Vec3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = self.numbers(line_number, line, &tokens, 3)?;
+                self.normals.push(Vec3::new(x, y, z));
+            }
+            "vt" => {
+                // La coordenada v es opcional en el formato
+                let [u, v] = self.numbers(line_number, line, &tokens, 1)?;
+                self.texcoords.push(Vec2::new(u, v));
+            }
+            "f" => self.face(line_number, line, &tokens)?,
+            "mtllib" => {
+                for &(column, file) in &tokens[1..] {
+                    self.material_library(line_number, column, file)?;
+                }
+            }
+            "usemtl" => match tokens.get(1) {
+                Some(&(column, name)) => match self.material_names.get(name) {
+                    Some(&index) => self.current_material = Some(index),
+                    None => {
+                        self.current_material = None;
+                        self.problem(line_number, column, format!("material desconocido '{}'", name))?;
+                    }
+                },
+                None => {
+                    let column = line.chars().count() + 1;
+                    self.problem(line_number, column, "'usemtl' necesita un nombre".to_string())?;
+                }
+            },
+            // Comentarios, grupos, suavizado y demás no afectan la geometría
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    // Lee N números tras la palabra clave; los primeros `required` son obligatorios
+    fn numbers<const N: usize>(
+        &mut self,
+        line_number: usize,
+        line: &str,
+        tokens: &[(usize, &str)],
+        required: usize,
+    ) -> Result<[f32; N], String> {
+        let mut values = [0.0; N];
+
+        for (i, value) in values.iter_mut().enumerate() {
+            match tokens.get(i + 1) {
+                Some(&(column, text)) => match text.parse::<f32>() {
+                    Ok(number) if number.is_finite() => *value = number,
+                    _ => self.problem(line_number, column, format!("número inválido '{}'", text))?,
+                },
+                None if i < required => {
+                    let column = line.chars().count() + 1;
+                    let message = format!("'{}' necesita al menos {} valores", tokens[0].1, required);
+                    self.problem(line_number, column, message)?;
+                }
+                None => {}
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn face(&mut self, line_number: usize, line: &str, tokens: &[(usize, &str)]) -> Result<(), String> {
+        if tokens.len() < 4 {
+            let column = line.chars().count() + 1;
+            return self.problem(line_number, column, "una cara necesita al menos 3 vértices".to_string());
+        }
+
+        let mut face_indices = Vec::with_capacity(tokens.len() - 1);
+        for &(column, text) in &tokens[1..] {
+            match self.face_vertex(column, text) {
+                Ok(index) => face_indices.push(index),
+                Err((column, message)) => {
+                    // En modo tolerante la cara se descarta entera
+                    return self.problem(line_number, column, message);
+                }
+            }
+        }
+
+        // Triangulación en abanico
+        let start = self.indices.len();
+        for i in 1..face_indices.len() - 1 {
+            self.indices.extend_from_slice(&[face_indices[0], face_indices[i], face_indices[i + 1]]);
+        }
+
+        // Las caras consecutivas con el mismo material comparten submalla
+        match self.submeshes.last_mut() {
+            Some(last) if last.material == self.current_material => last.indices.end = self.indices.len(),
+            _ => self.submeshes.push(SubMesh {
+                material: self.current_material,
+                indices: start..self.indices.len(),
+            }),
+        }
+
+        Ok(())
+    }
+
+    fn material_library(&mut self, line_number: usize, column: usize, file: &str) -> Result<(), String> {
+        let materials = match load_mtl(&self.base_dir.join(file)) {
+            Ok(materials) => materials,
+            Err(e) => return self.problem(line_number, column, e),
+        };
+
+        for material in materials {
+            self.material_names.insert(material.name.clone(), self.materials.len());
+            self.materials.push(material);
+        }
+
+        Ok(())
+    }
+
+    // Resuelve `v`, `v/vt`, `v//vn` o `v/vt/vn` al índice del vértice único
+    fn face_vertex(&mut self, column: usize, text: &str) -> Result<u32, (usize, String)> {
+        let parts: Vec<&str> = text.split('/').collect();
+        if parts.len() > 3 {
+            return Err((column, format!("vértice de cara inválido '{}'", text)));
+        }
+
+        // Columna de cada componente dentro del token
+        let mut columns = [column; 3];
+        for i in 1..parts.len() {
+            columns[i] = columns[i - 1] + parts[i - 1].chars().count() + 1;
+        }
+
+        let position = resolve_index(parts[0], self.positions.len(), "posición")
+            .map_err(|e| (columns[0], e))?;
+        let tex = match parts.get(1) {
+            Some(part) if !part.is_empty() => {
+                Some(resolve_index(part, self.texcoords.len(), "textura").map_err(|e| (columns[1], e))?)
+            }
+            _ => None,
+        };
+        let normal = match parts.get(2) {
+            Some(part) if !part.is_empty() => {
+                Some(resolve_index(part, self.normals.len(), "normal").map_err(|e| (columns[2], e))?)
+            }
+            _ => None,
+        };
+
+        let vertices = &mut self.vertices;
+        let missing_normals = &mut self.missing_normals;
+        let positions = &self.positions;
+        let texcoords = &self.texcoords;
+        let normals = &self.normals;
+
+        Ok(*self.unique.entry((position, tex, normal)).or_insert_with(|| {
+            let tex_coords = tex.map(|i| texcoords[i]).unwrap_or(Vec2::zeros());
+            missing_normals.push(normal.is_none());
+            let normal = normal.map(|i| normals[i]).unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+
+            vertices.push(Vertex::new(positions[position], normal, tex_coords));
+            (vertices.len() - 1) as u32
+        }))
+    }
+
+    // Error en modo estricto; advertencia en modo tolerante
+    fn problem(&mut self, line_number: usize, column: usize, message: String) -> Result<(), String> {
+        let located = format!("{}:{}:{}: {}", self.filename, line_number, column, message);
+        match self.mode {
+            ParseMode::Strict => Err(located),
+            ParseMode::Lenient => {
+                self.warnings.push(located);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Índice OBJ (desde 1, o negativo relativo al final) a índice desde 0
+fn resolve_index(text: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let value: i64 = text
+        .parse()
+        .map_err(|_| format!("índice de {} inválido '{}'", kind, text))?;
+
+    let resolved = match value {
+        0 => return Err(format!("índice de {} 0 inválido (los índices OBJ empiezan en 1)", kind)),
+        v if v > 0 => v - 1,
+        v => count as i64 + v,
+    };
+
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("índice de {} {} fuera de rango (hay {} definidos)", kind, value, count));
+    }
+
+    Ok(resolved as usize)
+}
+
+// Separa una línea en palabras junto con su columna (desde 1, en caracteres).
+// Todo lo que sigue a un '#' es comentario
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+
+    for (column, (byte, c)) in line.char_indices().enumerate() {
+        if c == '#' {
+            if let Some((start_byte, start_column)) = start.take() {
+                tokens.push((start_column, &line[start_byte..byte]));
+            }
+            return tokens;
+        }
+
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((byte, column + 1)),
+            (true, Some((start_byte, start_column))) => {
+                tokens.push((start_column, &line[start_byte..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((start_byte, start_column)) = start {
+        tokens.push((start_column, &line[start_byte..]));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_corners_are_stored_once() {
+        let (model, _) = Model::load_with_mode("sphere.obj", ParseMode::Strict).unwrap();
+
+        // sphere.obj: 1224 caras triangulares que usan 701 de sus 703 vértices
+        // (cada uno con la misma posición, uv y normal en todas sus caras)
+        assert_eq!(model.triangle_count(), 1224);
+        assert_eq!(model.vertices.len(), 701);
+        assert!(model.indices.iter().all(|&i| (i as usize) < model.vertices.len()));
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_the_end() {
+        let obj = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vt 0.5 0.5
+f -3/-1 -2/-1 -1/-1
+";
+        let (model, warnings) = Model::parse(obj, "tri.obj", ParseMode::Strict).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(model.indices, vec![0, 1, 2]);
+        assert_eq!(model.vertices[2].position, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(model.vertices[0].tex_coords, Vec2::new(0.5, 0.5));
+    }
+
+    #[test]
+    fn strict_errors_point_at_the_bad_token() {
+        let bad_number = "v 0 0 0\nv 1 0x 0\n";
+        assert_eq!(
+            Model::parse(bad_number, "a.obj", ParseMode::Strict).err().unwrap(),
+            "a.obj:2:5: número inválido '0x'"
+        );
+
+        let zero_index = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 0\n";
+        assert_eq!(
+            Model::parse(zero_index, "b.obj", ParseMode::Strict).err().unwrap(),
+            "b.obj:4:7: índice de posición 0 inválido (los índices OBJ empiezan en 1)"
+        );
+
+        let missing_normal = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//2\n";
+        assert_eq!(
+            Model::parse(missing_normal, "c.obj", ParseMode::Strict).err().unwrap(),
+            "c.obj:5:16: índice de normal 2 fuera de rango (hay 1 definidos)"
+        );
+    }
+
+    #[test]
+    fn lenient_mode_reports_and_skips() {
+        let obj = "\
+v 0 0 0
+v 1 nan 0
+v 0 1 0
+f 1 2 3
+f 1 2 9
+";
+        let (model, warnings) = Model::parse(obj, "d.obj", ParseMode::Lenient).unwrap();
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("d.obj:2:5:"));
+        assert!(warnings[1].starts_with("d.obj:5:7:"));
+
+        // La coordenada inválida vale 0 y la cara con índice inválido se descarta
+        assert_eq!(model.triangle_count(), 1);
+        assert_eq!(model.vertices[1].position, Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn usemtl_splits_submeshes_with_mtl_materials() {
+        let dir = std::env::temp_dir().join(format!("obj_mtl_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        image::save_buffer(dir.join("roca.png"), &[200, 100, 50, 10, 20, 30], 2, 1, image::ColorType::Rgb8).unwrap();
+        fs::write(dir.join("nave.mtl"), "\
+newmtl casco
+Kd 0.5 0.5 0.6
+Ks 1.0 1.0 1.0
+Ns 64
+newmtl roca
+Kd 1 1 1
+map_Kd roca.png
+map_Bump -bm 0.5 roca.png
+").unwrap();
+        let obj = "\
+mtllib nave.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 1 1 0
+f 1 2 3
+usemtl casco
+f 2 4 3
+f 1 2 4
+usemtl roca
+f 1 4 3
+";
+        let path = dir.join("nave.obj");
+        let (model, warnings) = Model::parse(obj, &path.to_string_lossy(), ParseMode::Strict).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!(model.submeshes, vec![
+            SubMesh { material: None, indices: 0..3 },
+            SubMesh { material: Some(0), indices: 3..9 },
+            SubMesh { material: Some(1), indices: 9..12 },
+        ]);
+
+        let casco = &model.materials[0];
+        assert_eq!(casco.name, "casco");
+        assert_eq!(casco.diffuse, Vec3::new(0.5, 0.5, 0.6));
+        assert_eq!(casco.specular, Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(casco.shininess, 64.0);
+        assert!(casco.diffuse_map.is_none());
+
+        let roca = &model.materials[1];
+        assert_eq!(roca.diffuse_map.as_ref().unwrap().texel(0, 0).to_hex(), 0xC86432);
+        assert_eq!(roca.bump_map.as_ref().unwrap().width, 2);
+    }
+
+    #[test]
+    fn unknown_material_is_located() {
+        let obj = "v 0 0 0\nusemtl  fantasma\n";
+        assert_eq!(
+            Model::parse(obj, "e.obj", ParseMode::Strict).err().unwrap(),
+            "e.obj:2:9: material desconocido 'fantasma'"
+        );
+    }
+
+    // Cubo de lado 2 sin `vn`, caras cuadradas en sentido antihorario
+    const CUBE: &str = "\
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+f 5 6 7 8
+f 2 1 4 3
+f 6 2 3 7
+f 1 5 8 4
+f 8 7 3 4
+f 1 2 6 5
+";
+
+    #[test]
+    fn missing_normals_are_smoothed_by_angle() {
+        let (model, _) = Model::parse(CUBE, "cubo.obj", ParseMode::Strict).unwrap();
+
+        // Cada esquina recibe la misma contribución de sus tres caras
+        for vertex in &model.vertices {
+            let expected = vertex.position.normalize();
+            assert!((vertex.normal - expected).magnitude() < 1e-5, "{:?}", vertex.normal);
+        }
+    }
+
+    #[test]
+    fn flat_normals_split_corners_per_face() {
+        let (mut model, _) = Model::parse(CUBE, "cubo.obj", ParseMode::Strict).unwrap();
+        model.generate_flat_normals();
+
+        assert_eq!(model.vertices.len(), 24);
+        assert_eq!(model.triangle_count(), 12);
+        for corners in model.triangles() {
+            let [a, b, c] = corners.map(|v| v.position);
+            let geometric = (b - a).cross(&(c - a)).normalize();
+            for vertex in corners {
+                assert_eq!(vertex.normal, geometric);
+            }
+        }
+    }
+
+    #[test]
+    fn tangents_follow_u_and_record_mirroring() {
+        let quad = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+        let (model, _) = Model::parse(quad, "quad.obj", ParseMode::Strict).unwrap();
+        for vertex in &model.vertices {
+            assert_eq!(vertex.tangent, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        }
+
+        // Con v invertida la bitangente apunta hacia -y
+        let mirrored = quad.replace("vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1", "vt 0 1\nvt 1 1\nvt 1 0\nvt 0 0");
+        let (model, _) = Model::parse(&mirrored, "quad.obj", ParseMode::Strict).unwrap();
+        for vertex in &model.vertices {
+            assert_eq!(vertex.tangent, Vec4::new(1.0, 0.0, 0.0, -1.0));
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::light::Light;
+
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// ============================================
+// SOMBRAS ANALÍTICAS ENTRE ESFERAS
+// ============================================
+
+// Máximo de cuerpos que hacen sombra: `Occluders` vive en `Uniforms`, que se copia por draw
+pub const MAX_OCCLUDERS: usize = 16;
+
+// Un punto a menos de esta fracción de más del radio de una esfera está sobre
+// su superficie: un cuerpo no se hace sombra a sí mismo (eso lo da Lambert)
+const SURFACE_MARGIN: f32 = 1.01;
+
+// Esfera que puede tapar la luz, en espacio de mundo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Occluder {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+// Cuerpos de un frame, de tamaño fijo como `Lights`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Occluders {
+    spheres: [Occluder; MAX_OCCLUDERS],
+    count: usize,
+}
+
+impl Occluders {
+    pub fn new() -> Self {
+        Occluders { spheres: [Occluder { center: Vec3::zeros(), radius: 0.0 }; MAX_OCCLUDERS], count: 0 }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Occluder> {
+        self.spheres[..self.count].iter()
+    }
+
+    // Fracción de `light` que llega a `point` con todas las esferas en medio
+    // (1 = iluminado, 0 = umbra). Las esferas se tratan por separado, así que
+    // dos que se solapan en el cielo oscurecen de más
+    pub fn visibility(&self, point: Vec3, light: &Light) -> f32 {
+        if self.count == 0 {
+            return 1.0;
+        }
+
+        let to_light = light.direction_from(point);
+        let light_distance = light.distance_from(point);
+        let light_angle = light.angular_radius_from(point);
+
+        self.iter().fold(1.0, |visible, occluder| {
+            visible * occluder.visibility(point, to_light, light_distance, light_angle)
+        })
+    }
+}
+
+impl Occluder {
+    // Fracción del disco de la luz (radio angular `light_angle`, en la
+    // dirección `to_light`) que no tapa esta esfera vista desde `point`
+    pub fn visibility(&self, point: Vec3, to_light: Vec3, light_distance: f32, light_angle: f32) -> f32 {
+        let offset = self.center - point;
+        let distance = offset.magnitude();
+
+        // El propio cuerpo o uno más allá de la luz
+        if distance <= self.radius * SURFACE_MARGIN || distance - self.radius >= light_distance {
+            return 1.0;
+        }
+        // La esfera que emite la luz (el sol) no se tapa a sí misma
+        if light_distance.is_finite() && (point + to_light * light_distance - self.center).magnitude() <= self.radius {
+            return 1.0;
+        }
+
+        let occluder_angle = (self.radius / distance).asin();
+        let separation = (offset.dot(&to_light) / distance).clamp(-1.0, 1.0).acos();
+        1.0 - disc_coverage(light_angle, occluder_angle, separation)
+    }
+}
+
+impl Default for Occluders {
+    fn default() -> Self {
+        Occluders::new()
+    }
+}
+
+// Los que excedan MAX_OCCLUDERS se descartan
+impl FromIterator<Occluder> for Occluders {
+    fn from_iter<I: IntoIterator<Item = Occluder>>(iter: I) -> Self {
+        let mut occluders = Occluders::new();
+        for occluder in iter.into_iter().take(MAX_OCCLUDERS) {
+            occluders.spheres[occluders.count] = occluder;
+            occluders.count += 1;
+        }
+        occluders
+    }
+}
+
+// Fracción de un disco de radio `light` tapada por otro de radio `occluder`
+// con los centros a `separation` (radios angulares, tomados como planos).
+// Sin solaparse no tapa nada; el tapador entero adentro deja un anillo
+// (eclipse anular) y uno más grande que cubre al otro es la umbra
+fn disc_coverage(light: f32, occluder: f32, separation: f32) -> f32 {
+    if separation >= light + occluder {
+        return 0.0;
+    }
+    if separation <= (light - occluder).abs() {
+        return if occluder >= light { 1.0 } else { (occluder / light).powi(2) };
+    }
+
+    // Área de la lente entre los dos círculos
+    let (d, r1, r2) = (separation, light, occluder);
+    let angle1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let angle2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let kite = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).max(0.0).sqrt() * 0.5;
+    let lens = r1 * r1 * angle1 + r2 * r2 * angle2 - kite;
+
+    (lens / (PI * r1 * r1)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::HdrColor;
+
+    #[test]
+    fn discs_cover_from_nothing_to_umbra() {
+        assert_eq!(disc_coverage(1.0, 0.5, 2.0), 0.0);
+        assert_eq!(disc_coverage(1.0, 2.0, 0.5), 1.0);
+        assert!((disc_coverage(1.0, 0.5, 0.0) - 0.25).abs() < 1e-6);
+
+        // Dos discos iguales con los centros en el borde del otro
+        let half_way = disc_coverage(1.0, 1.0, 1.0);
+        assert!((half_way - 0.391).abs() < 1e-3, "cobertura = {}", half_way);
+
+        // La penumbra crece a medida que el tapador se acerca al centro
+        let mut last = 0.0;
+        for step in (0..=20).rev() {
+            let coverage = disc_coverage(1.0, 1.2, step as f32 * 0.12);
+            assert!(coverage >= last);
+            last = coverage;
+        }
+        assert_eq!(last, 1.0);
+    }
+
+    #[test]
+    fn spheres_cast_umbra_and_penumbra() {
+        // Sol de radio 1 en x = 20; luna de radio 0.5 en x = 2
+        let sun = Light::point(Vec3::new(20.0, 0.0, 0.0), HdrColor::new(1.0, 1.0, 1.0), 1.0, 0.0).with_radius(1.0);
+        let moon = Occluder { center: Vec3::new(2.0, 0.0, 0.0), radius: 0.5 };
+        let occluders: Occluders = [moon].into_iter().collect();
+
+        assert_eq!(occluders.visibility(Vec3::zeros(), &sun), 0.0);
+        assert_eq!(occluders.visibility(Vec3::new(0.0, 2.0, 0.0), &sun), 1.0);
+        let penumbra = occluders.visibility(Vec3::new(0.0, 0.55, 0.0), &sun);
+        assert!(penumbra > 0.0 && penumbra < 1.0, "penumbra = {}", penumbra);
+
+        // Ni la propia superficie ni lo que está detrás de la luz hacen sombra
+        assert_eq!(occluders.visibility(Vec3::new(1.5, 0.0, 0.0), &sun), 1.0);
+        let beyond: Occluders = [Occluder { center: Vec3::new(30.0, 0.0, 0.0), radius: 2.0 }].into_iter().collect();
+        assert_eq!(beyond.visibility(Vec3::zeros(), &sun), 1.0);
+
+        // El sol como esfera no tapa su propia luz
+        let with_sun: Occluders = [Occluder { center: Vec3::new(20.0, 0.0, 0.0), radius: 1.0 }].into_iter().collect();
+        assert_eq!(with_sun.visibility(Vec3::zeros(), &sun), 1.0);
+    }
+}
@@ -1,4 +1,41 @@
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::obj_loader::Model;
+use crate::lod::LodChain;
+use std::sync::Arc;
+use crate::primitives::create_ring_model;
+use crate::shaders::RingShadow;
+use crate::atmosphere::Atmosphere;
+use crate::glow::Glow;
+
+#[derive(Clone)]
+pub struct Ring {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub tilt: f32,
+    pub model: Model,
+}
+
+impl Ring {
+    pub fn new(inner_radius: f32, outer_radius: f32, tilt: f32) -> Self {
+        Ring {
+            inner_radius,
+            outer_radius,
+            tilt,
+            model: create_ring_model(inner_radius, outer_radius, 100),
+        }
+    }
+
+    // El plano del anillo tal como queda dibujado con `model_matrix`
+    pub fn shadow(&self, model_matrix: &Mat4) -> RingShadow {
+        let scale = (model_matrix * Vec4::new(1.0, 0.0, 0.0, 0.0)).xyz().magnitude();
+        RingShadow {
+            center: (model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz(),
+            normal: (model_matrix * Vec4::new(0.0, 1.0, 0.0, 0.0)).xyz().normalize(),
+            inner_radius: self.inner_radius * scale,
+            outer_radius: self.outer_radius * scale,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Planet {
@@ -11,6 +48,16 @@ pub struct Planet {
     pub orbit_radius: f32,
     pub orbit_speed: f32,
     pub orbit_angle: f32,
+    // Escala y radio orbital de las lunas son relativos a la escala del padre
+    pub moons: Vec<Planet>,
+    pub ring: Option<Ring>,
+    // Cáscara de aire que se dibuja alrededor del cuerpo
+    pub atmosphere: Option<Atmosphere>,
+    // Halo que suma luz alrededor de un cuerpo emisor
+    pub glow: Option<Glow>,
+    // Malla propia (asteroides, naves, otra teselación) con sus niveles de
+    // detalle; sin ella se usa la esfera compartida
+    pub lod: Option<Arc<LodChain>>,
 }
 
 impl Planet {
@@ -31,6 +78,52 @@ impl Planet {
             orbit_radius,
             orbit_speed,
             orbit_angle: 0.0,
+            moons: Vec::new(),
+            ring: None,
+            atmosphere: None,
+            glow: None,
+            lod: None,
+        }
+    }
+
+    pub fn with_moon(mut self, moon: Planet) -> Self {
+        self.moons.push(moon);
+        self
+    }
+
+    pub fn with_rotation_speed(mut self, rotation_speed: Vec3) -> Self {
+        self.rotation_speed = rotation_speed;
+        self
+    }
+
+    pub fn with_ring(mut self, ring: Ring) -> Self {
+        self.ring = Some(ring);
+        self
+    }
+
+    pub fn with_atmosphere(mut self, atmosphere: Atmosphere) -> Self {
+        self.atmosphere = Some(atmosphere);
+        self
+    }
+
+    pub fn with_glow(mut self, glow: Glow) -> Self {
+        self.glow = Some(glow);
+        self
+    }
+
+    pub fn with_lod(mut self, lod: Arc<LodChain>) -> Self {
+        self.lod = Some(lod);
+        self
+    }
+
+    // Vuelve al estado de t = 0 (útil para calcular una pose en un tiempo absoluto)
+    pub fn reset(&mut self) {
+        self.rotation = Vec3::zeros();
+        self.orbit_angle = 0.0;
+        self.position = Vec3::zeros();
+
+        for moon in &mut self.moons {
+            moon.reset();
         }
     }
 
@@ -44,17 +137,47 @@ impl Planet {
         // Calcular posición en órbita
         self.position.x = self.orbit_angle.cos() * self.orbit_radius;
         self.position.z = self.orbit_angle.sin() * self.orbit_radius;
+
+        // Las lunas orbitan relativo a su planeta
+        for moon in &mut self.moons {
+            moon.update(delta_time);
+        }
     }
 }
 
 // Función helper para crear todos los planetas del sistema solar
 pub fn create_solar_system() -> Vec<Planet> {
     vec![
-        Planet::new("Sol", "sun", 2.0, 0.0, 0.0),
+        Planet::new("Sol", "sun", 2.0, 0.0, 0.0).with_glow(Glow::sun()),
         Planet::new("Marte", "rocky_mars", 0.5, 3.0, 0.5),
-        Planet::new("Tierra", "rocky_earth", 0.6, 4.0, 0.4),
-        Planet::new("Júpiter", "gas_jupiter", 1.2, 6.0, 0.2),
-        Planet::new("Saturno", "gas_saturn", 1.0, 8.0, 0.15),
-        Planet::new("Neptuno", "ice_neptune", 0.7, 10.0, 0.1),
+        Planet::new("Tierra", "rocky_earth", 0.6, 5.0, 0.4)
+            .with_atmosphere(Atmosphere::earth())
+            .with_moon(
+                Planet::new("Luna", "moon", 0.27, 2.5, 0.5)
+                    .with_rotation_speed(Vec3::new(0.0, 0.1, 0.0)),
+            ),
+        Planet::new("Júpiter", "gas_jupiter", 1.2, 8.0, 0.2),
+        Planet::new("Saturno", "gas_saturn", 1.0, 11.0, 0.15)
+            .with_ring(Ring::new(1.2, 1.8, 0.4)),
+        Planet::new("Neptuno", "ice_neptune", 0.7, 14.0, 0.1)
+            .with_atmosphere(Atmosphere::neptune()),
     ]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaders::create_model_matrix;
+
+    #[test]
+    fn ring_shadow_follows_the_model_matrix() {
+        let ring = Ring::new(1.2, 1.8, 0.0);
+        let shadow = ring.shadow(&create_model_matrix(Vec3::new(1.0, 2.0, 3.0), 2.0, Vec3::new(0.0, 0.0, std::f32::consts::FRAC_PI_2)));
+
+        assert!((shadow.center - Vec3::new(1.0, 2.0, 3.0)).magnitude() < 1e-5);
+        assert!((shadow.normal.magnitude() - 1.0).abs() < 1e-5);
+        assert!(shadow.normal.y.abs() < 1e-5, "el anillo inclinado 90° queda vertical");
+        assert!((shadow.inner_radius - 2.4).abs() < 1e-5);
+        assert!((shadow.outer_radius - 3.6).abs() < 1e-5);
+    }
+}
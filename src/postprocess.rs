@@ -0,0 +1,194 @@
+use crate::color::HdrColor;
+use crate::framebuffer::Framebuffer;
+
+// ============================================
+// POST-PROCESO (sobre el buffer HDR, antes del tone mapping)
+// ============================================
+
+// Efectos que corren después de todos los draws del frame, en orden
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PostProcessing {
+    pub bloom: Bloom,
+}
+
+impl PostProcessing {
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.bloom.enabled {
+            self.bloom.apply(framebuffer);
+        }
+    }
+}
+
+// ============================================
+// BLOOM: umbral + desenfoque gaussiano separable
+// ============================================
+
+// Lo que supera `threshold` (luminancia lineal) se desenfoca con un radio de
+// `radius` píxeles y se suma a la imagen multiplicado por `intensity`: el sol
+// y los reflejos especulares derraman luz sobre lo que los rodea y el fondo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bloom {
+    pub enabled: bool,
+    pub threshold: f32,
+    pub intensity: f32,
+    pub radius: usize,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            enabled: true,
+            threshold: 1.0,
+            intensity: 0.6,
+            radius: 24,
+        }
+    }
+}
+
+impl Bloom {
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        if self.radius == 0 || self.intensity <= 0.0 {
+            return;
+        }
+
+        let width = framebuffer.width;
+        let height = framebuffer.height;
+
+        let bright = self.bright_pass(&framebuffer.hdr_buffer);
+        if bright.iter().all(|color| *color == HdrColor::black()) {
+            return;
+        }
+
+        let kernel = gaussian_kernel(self.radius);
+        let mut horizontal = vec![HdrColor::black(); width * height];
+        let mut blurred = vec![HdrColor::black(); width * height];
+
+        // Horizontal y vertical por separado: 2(2r+1) muestras por píxel en vez de (2r+1)^2
+        for_each_row(&mut horizontal, width, |y, row| {
+            let source = &bright[y * width..(y + 1) * width];
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = convolve(&kernel, |offset| source[clamp_index(x, offset, width)]);
+            }
+        });
+        for_each_row(&mut blurred, width, |y, row| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = convolve(&kernel, |offset| horizontal[clamp_index(y, offset, height) * width + x]);
+            }
+        });
+
+        for (pixel, glow) in framebuffer.hdr_buffer.iter_mut().zip(&blurred) {
+            *pixel += *glow * self.intensity;
+        }
+    }
+
+    // Conserva solo el exceso sobre el umbral, con el tono del píxel original
+    fn bright_pass(&self, hdr: &[HdrColor]) -> Vec<HdrColor> {
+        hdr.iter()
+            .map(|color| {
+                let luminance = color.luminance();
+                if luminance.is_finite() && luminance > self.threshold {
+                    *color * ((luminance - self.threshold) / luminance)
+                } else {
+                    HdrColor::black()
+                }
+            })
+            .collect()
+    }
+}
+
+// Pesos normalizados de -radius a radius; el radio cubre tres desviaciones estándar
+fn gaussian_kernel(radius: usize) -> Vec<f32> {
+    let sigma = radius as f32 / 3.0;
+    let weights: Vec<f32> = (0..=2 * radius)
+        .map(|i| {
+            let x = i as f32 - radius as f32;
+            (-(x * x) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+fn convolve(kernel: &[f32], sample: impl Fn(isize) -> HdrColor) -> HdrColor {
+    let radius = (kernel.len() / 2) as isize;
+    kernel
+        .iter()
+        .enumerate()
+        .fold(HdrColor::black(), |sum, (i, weight)| sum + sample(i as isize - radius) * *weight)
+}
+
+// Fuera de la imagen se repite el borde
+fn clamp_index(center: usize, offset: isize, len: usize) -> usize {
+    (center as isize + offset).clamp(0, len as isize - 1) as usize
+}
+
+// Reparte las filas de `buffer` entre los núcleos disponibles
+fn for_each_row<F>(buffer: &mut [HdrColor], width: usize, visit: F)
+where
+    F: Fn(usize, &mut [HdrColor]) + Sync,
+{
+    let height = buffer.len() / width;
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(height.max(1));
+    let rows_per_thread = height.div_ceil(threads);
+
+    std::thread::scope(|scope| {
+        for (chunk_index, chunk) in buffer.chunks_mut(rows_per_thread * width).enumerate() {
+            let visit = &visit;
+            scope.spawn(move || {
+                for (i, row) in chunk.chunks_mut(width).enumerate() {
+                    visit(chunk_index * rows_per_thread + i, row);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framebuffer_with(pixels: &[(usize, usize, HdrColor)]) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(64, 48);
+        for pixel in framebuffer.hdr_buffer.iter_mut() {
+            *pixel = HdrColor::new(0.2, 0.2, 0.2);
+        }
+        for &(x, y, color) in pixels {
+            framebuffer.hdr_buffer[y * 64 + x] = color;
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn dim_images_are_left_untouched() {
+        let mut framebuffer = framebuffer_with(&[(10, 10, HdrColor::new(0.9, 0.9, 0.9))]);
+        let before = framebuffer.hdr_buffer.clone();
+
+        Bloom::default().apply(&mut framebuffer);
+        assert!(framebuffer.hdr_buffer == before);
+    }
+
+    #[test]
+    fn bright_pixels_spread_symmetrically_and_keep_their_energy() {
+        let bloom = Bloom { enabled: true, threshold: 1.0, intensity: 1.0, radius: 6 };
+        let mut framebuffer = framebuffer_with(&[(32, 24, HdrColor::new(5.0, 5.0, 5.0))]);
+        let before = framebuffer.hdr_buffer.clone();
+
+        bloom.apply(&mut framebuffer);
+
+        let glow = |x: usize, y: usize| framebuffer.hdr_buffer[y * 64 + x].r - before[y * 64 + x].r;
+        assert!(glow(35, 24) > 0.0);
+        assert!((glow(35, 24) - glow(29, 24)).abs() < 1e-6);
+        assert!((glow(32, 27) - glow(32, 21)).abs() < 1e-6);
+        assert!((glow(35, 24) - glow(32, 27)).abs() < 1e-6);
+        assert_eq!(glow(32 + 7, 24), 0.0);
+
+        // Lejos de los bordes el kernel normalizado no pierde luz: se suma
+        // exactamente el exceso sobre el umbral
+        let added: f32 = (0..48).flat_map(|y| (0..64).map(move |x| (x, y))).map(|(x, y)| glow(x, y)).sum();
+        assert!((added - 4.0).abs() < 1e-3, "se agregaron {}", added);
+    }
+}
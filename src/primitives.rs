@@ -0,0 +1,300 @@
+use crate::obj_loader::Model;
+use crate::vertex::Vertex;
+
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+// Radio de las esferas integradas: el mismo tamaño que tenía sphere.obj
+// normalizado, así `scale = 1` no cambia de significado
+pub const SPHERE_RADIUS: f32 = 0.75;
+
+// ============================================
+// ESFERA UV
+// ============================================
+
+// `segments` divisiones en longitud y `rings` en latitud. La costura en
+// u = 0/1 duplica vértices para que las coordenadas de textura no se enrollen
+pub fn create_uv_sphere(radius: f32, segments: u32, rings: u32) -> Model {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        let theta = v * PI;
+
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let phi = u * 2.0 * PI;
+
+            let normal = Vec3::new(theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin());
+            vertices.push(Vertex::new(normal * radius, normal, Vec2::new(u, 1.0 - v)));
+        }
+    }
+
+    let stride = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let top_left = ring * stride + segment;
+            let top_right = top_left + 1;
+            let bottom_left = top_left + stride;
+            let bottom_right = bottom_left + 1;
+
+            // En los polos uno de los dos triángulos del cuadrilátero es degenerado
+            if ring != 0 {
+                indices.extend_from_slice(&[top_left, bottom_left, top_right]);
+            }
+            if ring != rings - 1 {
+                indices.extend_from_slice(&[top_right, bottom_left, bottom_right]);
+            }
+        }
+    }
+
+    let mut model = Model::new(vertices, indices);
+    model.generate_tangents();
+    model
+}
+
+// ============================================
+// ICOSFERA
+// ============================================
+
+// Icosaedro subdividido `subdivisions` veces (20 * 4^n triángulos): reparte
+// los vértices de forma más uniforme que la esfera UV, sin concentrarlos en los polos
+pub fn create_icosphere(radius: f32, subdivisions: u32) -> Model {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Cada arista se parte una sola vez aunque la compartan dos caras
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| -> u32 {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let middle = (positions[a as usize] + positions[b as usize]).normalize();
+                positions.push(middle);
+                (positions.len() - 1) as u32
+            })
+        };
+
+        let mut subdivided = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut positions);
+            let bc = midpoint(b, c, &mut positions);
+            let ca = midpoint(c, a, &mut positions);
+            subdivided.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = subdivided;
+    }
+
+    let spherical_uv = |p: &Vec3| Vec2::new(0.5 + (-p.z).atan2(p.x) / (2.0 * PI), 0.5 + p.y.asin() / PI);
+
+    let mut vertices: Vec<Vertex> = positions
+        .iter()
+        .map(|p| Vertex::new(p * radius, *p, spherical_uv(p)))
+        .collect();
+
+    // Los triángulos que cruzan la costura usan copias con u + 1
+    let mut seam_copies: HashMap<u32, u32> = HashMap::new();
+    let mut indices = Vec::with_capacity(faces.len() * 3);
+    for face in faces {
+        let us = face.map(|i| vertices[i as usize].tex_coords.x);
+        let max_u = us.iter().cloned().fold(f32::MIN, f32::max);
+
+        for (i, &index) in face.iter().enumerate() {
+            if max_u - us[i] > 0.5 {
+                let copy = *seam_copies.entry(index).or_insert_with(|| {
+                    let mut vertex = vertices[index as usize].clone();
+                    vertex.tex_coords.x += 1.0;
+                    vertices.push(vertex);
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(copy);
+            } else {
+                indices.push(index);
+            }
+        }
+    }
+
+    let mut model = Model::new(vertices, indices);
+    model.generate_tangents();
+    model
+}
+
+// ============================================
+// TORO
+// ============================================
+
+// Toro en el plano XZ: `major_radius` hasta el centro del tubo, `minor_radius`
+// del tubo; `segments` alrededor del eje y y `sides` alrededor del tubo
+pub fn create_torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> Model {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let phi = u * 2.0 * PI;
+        let direction = Vec3::new(phi.cos(), 0.0, -phi.sin());
+
+        for side in 0..=sides {
+            let v = side as f32 / sides as f32;
+            let theta = v * 2.0 * PI;
+
+            let normal = direction * theta.cos() + Vec3::new(0.0, theta.sin(), 0.0);
+            let position = direction * major_radius + normal * minor_radius;
+            vertices.push(Vertex::new(position, normal, Vec2::new(u, v)));
+        }
+    }
+
+    let stride = sides + 1;
+    for segment in 0..segments {
+        for side in 0..sides {
+            let a = segment * stride + side;
+            let b = a + stride;
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    let mut model = Model::new(vertices, indices);
+    model.generate_tangents();
+    model
+}
+
+// ============================================
+// ANILLO
+// ============================================
+
+// Coordenadas de textura en espacio del anillo: u es radial (0 en el borde
+// interior, 1 en el exterior) y v es el ángulo sobre 2π, así las bandas no
+// dependen de dónde cae el anillo en pantalla
+pub fn create_ring_model(inner_radius: f32, outer_radius: f32, segments: u32) -> Model {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..segments {
+        let angle1 = (i as f32 / segments as f32) * 2.0 * PI;
+        let angle2 = ((i + 1) as f32 / segments as f32) * 2.0 * PI;
+
+        let v1 = i as f32 / segments as f32;
+        let v2 = (i + 1) as f32 / segments as f32;
+
+        let cos1 = angle1.cos();
+        let sin1 = angle1.sin();
+        let cos2 = angle2.cos();
+        let sin2 = angle2.sin();
+
+        // Cada segmento tiene sus propios 4 vértices: el último cierra en
+        // v = 1 sin compartir vértices con el primero (v = 0)
+        let base = vertices.len() as u32;
+        vertices.push(Vertex::new(
+            Vec3::new(cos1 * inner_radius, 0.0, sin1 * inner_radius),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec2::new(0.0, v1),
+        ));
+        vertices.push(Vertex::new(
+            Vec3::new(cos1 * outer_radius, 0.0, sin1 * outer_radius),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec2::new(1.0, v1),
+        ));
+        vertices.push(Vertex::new(
+            Vec3::new(cos2 * inner_radius, 0.0, sin2 * inner_radius),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec2::new(0.0, v2),
+        ));
+        vertices.push(Vertex::new(
+            Vec3::new(cos2 * outer_radius, 0.0, sin2 * outer_radius),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec2::new(1.0, v2),
+        ));
+
+        // Triángulo 1
+        indices.extend_from_slice(&[base, base + 1, base + 2]);
+        // Triángulo 2
+        indices.extend_from_slice(&[base + 2, base + 1, base + 3]);
+    }
+
+    Model::new(vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Todas las caras deben ser antihorarias vistas desde afuera (las ve el
+    // back-face culling) y las normales de los vértices deben apuntar afuera
+    fn assert_outward(model: &Model, center_of: impl Fn(&Vec3) -> Vec3) {
+        for corners in model.triangles() {
+            let [a, b, c] = corners.map(|v| v.position);
+            let geometric = (b - a).cross(&(c - a));
+            assert!(geometric.magnitude() > 0.0, "triángulo degenerado");
+
+            let centroid = (a + b + c) / 3.0;
+            assert!(geometric.dot(&(centroid - center_of(&centroid))) > 0.0, "cara invertida en {:?}", centroid);
+
+            for vertex in corners {
+                assert!((vertex.normal.magnitude() - 1.0).abs() < 1e-4);
+                assert!(vertex.normal.dot(&geometric) > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn uv_sphere_is_closed_and_outward() {
+        let model = create_uv_sphere(SPHERE_RADIUS, 16, 8);
+        // Dos triángulos por cuadrilátero salvo en los polos
+        assert_eq!(model.triangle_count(), 16 * (8 * 2 - 2));
+        assert_outward(&model, |_| Vec3::zeros());
+
+        for vertex in &model.vertices {
+            assert!((vertex.position.magnitude() - SPHERE_RADIUS).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn icosphere_subdivides_and_splits_the_seam() {
+        for subdivisions in 0..3 {
+            let model = create_icosphere(1.0, subdivisions);
+            assert_eq!(model.triangle_count(), 20 * 4usize.pow(subdivisions));
+            assert_outward(&model, |_| Vec3::zeros());
+
+            for corners in model.triangles() {
+                let us = corners.map(|v| v.tex_coords.x);
+                let span = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min);
+                assert!(span <= 0.5, "triángulo envuelto en la costura");
+            }
+        }
+    }
+
+    #[test]
+    fn torus_faces_away_from_the_tube_center() {
+        let model = create_torus(1.0, 0.25, 24, 12);
+        assert_eq!(model.triangle_count(), 24 * 12 * 2);
+        assert_outward(&model, |p| Vec3::new(p.x, 0.0, p.z).normalize());
+    }
+
+    #[test]
+    fn ring_uvs_follow_radius_and_angle() {
+        let model = create_ring_model(1.2, 1.8, 16);
+        for vertex in &model.vertices {
+            let radius = Vec2::new(vertex.position.x, vertex.position.z).magnitude();
+            assert!((radius - (1.2 + 0.6 * vertex.tex_coords.x)).abs() < 1e-5);
+
+            let angle = vertex.tex_coords.y * 2.0 * PI;
+            assert!((vertex.position.x - angle.cos() * radius).abs() < 1e-4);
+            assert!((vertex.position.z - angle.sin() * radius).abs() < 1e-4);
+        }
+    }
+}
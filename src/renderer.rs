@@ -0,0 +1,994 @@
+use crate::framebuffer::Framebuffer;
+use crate::color::{HdrColor, Rgba};
+use crate::triangle::{PixelRect, Triangle, Winding};
+use crate::clipping::clip_triangle;
+use crate::obj_loader::Model;
+use crate::primitives::SPHERE_RADIUS;
+use crate::lod::{LodChain, LodState};
+use crate::material::{Material, MaterialShader, MATERIAL_SHADER};
+use crate::vertex::Vertex;
+use crate::shaders::{create_model_matrix, create_viewport_matrix, RingShadow, Uniforms};
+use crate::fragment::Fragment;
+use crate::light::Lights;
+use crate::occlusion::Occluders;
+use crate::shadow::{ShadowMap, ShadowMethod, SHADOW_MAP_SIZE};
+use crate::shader_registry::{ShaderRegistry, VertexShader, FragmentShader, DEFAULT_VERTEX_SHADER};
+use crate::camera::{Camera, NEAR_PLANE};
+use crate::planet::Planet;
+use crate::scene::{occluders, world_position, Scene};
+
+use nalgebra_glm::{Vec3, Vec4, Mat4};
+use std::sync::Mutex;
+
+// Tamaño (en píxeles) de los tiles en que se divide la pantalla
+pub const TILE_SIZE: usize = 32;
+
+// Con menos píxeles que esto (sumando las cajas envolventes) el draw se
+// rasteriza en el hilo actual: lanzar los hilos costaría más que el trabajo,
+// como en las lunas lejanas o los cuerpos de pocos píxeles
+const PARALLEL_MIN_PIXELS: usize = 4 * TILE_SIZE * TILE_SIZE;
+
+// Teselado de la esfera compartida
+const DEFAULT_SPHERE_SEGMENTS: u32 = 48;
+const DEFAULT_SPHERE_RINGS: u32 = 24;
+
+// Cómo se combina el color de un fragmento con el que ya está en el framebuffer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    // Reemplaza (la opacidad se ignora)
+    Opaque,
+    // destino * (1 - a) + fuente * a
+    Alpha,
+    // destino + fuente * a: brillos y capas que solo suman luz
+    Additive,
+    // destino * (1 - a) + fuente: la fuente ya viene multiplicada por su
+    // cobertura, como la luz que dispersa una atmósfera sobre lo que atenúa
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub fn blend(&self, destination: HdrColor, source: Rgba) -> HdrColor {
+        match self {
+            BlendMode::Opaque => source.color,
+            BlendMode::Alpha => destination.lerp(&source.color, source.alpha.clamp(0.0, 1.0)),
+            BlendMode::Additive => destination + source.color * source.alpha.max(0.0),
+            BlendMode::Premultiplied => destination * (1.0 - source.alpha.clamp(0.0, 1.0)) + source.color,
+        }
+    }
+}
+
+// Estado fijo de cada llamada a `render`
+#[derive(Clone, Copy, Debug)]
+pub struct DrawOptions {
+    pub front_face: Winding,
+    pub cull_back_faces: bool,
+    pub blend: BlendMode,
+    // Sin escritura de profundidad lo que se dibuja después (otras capas
+    // translúcidas) no queda tapado por esta capa
+    pub depth_write: bool,
+    // Sin test de profundidad la capa se dibuja aunque algo ya la tape
+    pub depth_test: bool,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        DrawOptions {
+            front_face: Winding::CounterClockwise,
+            cull_back_faces: true,
+            blend: BlendMode::Opaque,
+            depth_write: true,
+            depth_test: true,
+        }
+    }
+}
+
+impl DrawOptions {
+    // Geometría plana visible desde ambos lados, como los anillos
+    pub fn double_sided() -> Self {
+        DrawOptions { cull_back_faces: false, ..DrawOptions::default() }
+    }
+
+    // Capa translúcida: mezcla por opacidad y no escribe profundidad
+    pub fn translucent(self) -> Self {
+        DrawOptions { blend: BlendMode::Alpha, depth_write: false, ..self }
+    }
+}
+
+pub fn render(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    model: &Model,
+    vertex_shader: &dyn VertexShader,
+    fragment_shader: &dyn FragmentShader,
+    options: &DrawOptions,
+) {
+    let transformed_vertices = transform_vertices(model, uniforms, vertex_shader);
+    draw_indexed(framebuffer, uniforms, &transformed_vertices, &model.indices, fragment_shader, options);
+}
+
+// Dibuja cada submalla con un MaterialShader de su material (MTL)
+pub fn render_with_materials(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    model: &Model,
+    vertex_shader: &dyn VertexShader,
+    options: &DrawOptions,
+    receiver: &ShadowReceiver,
+) {
+    let transformed_vertices = transform_vertices(model, uniforms, vertex_shader);
+    let default_material = Material::default();
+
+    for submesh in &model.submeshes {
+        let material = submesh
+            .material
+            .map_or(&default_material, |index| &model.materials[index]);
+        let indices = &model.indices[submesh.indices.clone()];
+
+        let shader = MaterialShader::new(material);
+        draw_indexed(framebuffer, uniforms, &transformed_vertices, indices, &receiver.wrap(&shader), options);
+    }
+}
+
+// ============================================
+// PASADA TRANSLÚCIDA
+// ============================================
+
+// Un draw con mezcla, diferido hasta que toda la geometría opaca está en el
+// z-buffer: así lo que queda detrás de una capa translúcida ya está dibujado
+pub struct TransparentDraw<'a> {
+    pub model: &'a Model,
+    pub fragment_shader: &'a dyn FragmentShader,
+    pub uniforms: Uniforms,
+    pub options: DrawOptions,
+    // Distancia de la cámara al centro del objeto
+    pub distance: f32,
+}
+
+#[derive(Default)]
+pub struct TransparentQueue<'a> {
+    draws: Vec<TransparentDraw<'a>>,
+}
+
+impl<'a> TransparentQueue<'a> {
+    pub fn new() -> Self {
+        TransparentQueue { draws: Vec::new() }
+    }
+
+    pub fn push(
+        &mut self,
+        model: &'a Model,
+        fragment_shader: &'a dyn FragmentShader,
+        uniforms: Uniforms,
+        options: DrawOptions,
+        center: Vec3,
+    ) {
+        let view_position = uniforms.view_matrix * Vec4::new(center.x, center.y, center.z, 1.0);
+        let distance = view_position.xyz().magnitude();
+        self.draws.push(TransparentDraw { model, fragment_shader, uniforms, options, distance });
+    }
+
+    // Dibuja de atrás hacia adelante: cada capa se mezcla sobre las más lejanas
+    pub fn flush(mut self, framebuffer: &mut Framebuffer, vertex_shader: &dyn VertexShader) {
+        self.draws.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+        for draw in &self.draws {
+            render(framebuffer, &draw.uniforms, draw.model, vertex_shader, draw.fragment_shader, &draw.options);
+        }
+    }
+}
+
+// ============================================
+// SOMBRAS ENTRE CUERPOS
+// ============================================
+
+// Sombras que recibe un cuerpo: la de su anillo y el mapa de sombras de su
+// sistema (el planeta y sus lunas)
+#[derive(Clone, Copy, Default)]
+pub struct ShadowReceiver<'a> {
+    pub ring: Option<RingShadow>,
+    pub shadow_map: Option<&'a ShadowMap>,
+}
+
+impl<'a> ShadowReceiver<'a> {
+    pub fn wrap<'b>(&'b self, shader: &'b dyn FragmentShader) -> Shadowed<'b> {
+        Shadowed { shader, receiver: self }
+    }
+}
+
+// Adapta cualquier shader: en la sombra del mapa atenúa la luz principal antes
+// de sombrear (el ambiente no cambia) y después oscurece con la del anillo
+pub struct Shadowed<'a> {
+    shader: &'a dyn FragmentShader,
+    receiver: &'a ShadowReceiver<'a>,
+}
+
+impl FragmentShader for Shadowed<'_> {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        let visibility = self
+            .receiver
+            .shadow_map
+            .map_or(1.0, |map| map.visibility(fragment.world_position, fragment.normal.normalize()));
+
+        let shaded = if visibility < 1.0 {
+            let in_shadow = Uniforms { lights: uniforms.lights.shadowed(visibility), ..*uniforms };
+            self.shader.shade(fragment, &in_shadow)
+        } else {
+            self.shader.shade(fragment, uniforms)
+        };
+
+        match &self.receiver.ring {
+            Some(ring) => {
+                let light_dir = uniforms.lights.main_direction(fragment.world_position);
+                let light = ring.light_at(fragment.world_position, light_dir);
+                Rgba::new(shaded.color * light, shaded.alpha)
+            }
+            None => shaded,
+        }
+    }
+}
+
+// Un cuerpo tal como se dibuja en el mapa de sombras
+struct Caster<'a> {
+    model: &'a Model,
+    model_matrix: Mat4,
+    center: Vec3,
+    radius: f32,
+}
+
+fn collect_casters<'a>(assets: &'a SceneAssets, planet: &'a Planet, origin: Vec3, parent_scale: f32, out: &mut Vec<Caster<'a>>) {
+    let position = origin + planet.position * parent_scale;
+    let scale = planet.scale * parent_scale;
+    let chain = planet.lod.as_deref().unwrap_or(&assets.planet_lod);
+
+    out.push(Caster {
+        model: chain.finest(),
+        model_matrix: create_model_matrix(position, scale, planet.rotation),
+        center: position,
+        radius: chain.bounding_radius() * scale,
+    });
+    for moon in &planet.moons {
+        collect_casters(assets, moon, position, scale, out);
+    }
+}
+
+// Mapa de sombras de un planeta y sus lunas desde la luz principal, para
+// eclipses y tránsitos dentro de ese sistema; los anillos no proyectan aquí
+fn build_shadow_map(assets: &SceneAssets, planet: &Planet, origin: Vec3, parent_scale: f32, lights: &Lights) -> Option<ShadowMap> {
+    let center = origin + planet.position * parent_scale;
+    let light_dir = lights.main_direction(center);
+    if light_dir == Vec3::zeros() {
+        return None;
+    }
+
+    let mut casters = Vec::new();
+    collect_casters(assets, planet, origin, parent_scale, &mut casters);
+    let radius = casters
+        .iter()
+        .map(|caster| (caster.center - center).magnitude() + caster.radius)
+        .fold(0.0, f32::max);
+
+    let mut shadow_map = ShadowMap::new(SHADOW_MAP_SIZE);
+    shadow_map.fit(center, radius, light_dir);
+    for caster in &casters {
+        shadow_map.render_depth(caster.model, &caster.model_matrix);
+    }
+    Some(shadow_map)
+}
+
+// Cada vértice único pasa una sola vez por el vertex shader
+fn transform_vertices(model: &Model, uniforms: &Uniforms, vertex_shader: &dyn VertexShader) -> Vec<Vertex> {
+    model
+        .vertices
+        .iter()
+        .map(|vertex| vertex_shader.transform(vertex, uniforms))
+        .collect()
+}
+
+// Ensamblado desde los índices, recorte en espacio de clip, división por w y
+// viewport, culling y rasterizado
+fn draw_indexed(
+    framebuffer: &mut Framebuffer,
+    uniforms: &Uniforms,
+    transformed_vertices: &[Vertex],
+    indices: &[u32],
+    fragment_shader: &dyn FragmentShader,
+    options: &DrawOptions,
+) {
+    let mut triangles = Vec::with_capacity(indices.len() / 3);
+    for triangle in indices.chunks_exact(3) {
+        let vertices = [0, 1, 2].map(|i| &transformed_vertices[triangle[i] as usize]);
+        clip_triangle(vertices, &uniforms.viewport_matrix, &mut triangles);
+    }
+
+    if options.cull_back_faces {
+        triangles.retain(|triangle| triangle.is_front_facing(options.front_face));
+    }
+
+    rasterize_tiled(framebuffer, &triangles, uniforms, fragment_shader, options);
+}
+
+// Agrupa los triángulos por tile según su caja envolvente y procesa cada fila
+// de tiles en paralelo. Dentro de un tile los triángulos conservan su orden,
+// así que cada píxel recibe la misma secuencia de fragmentos que en serie.
+// Los draws chicos se hacen en el hilo actual (ver PARALLEL_MIN_PIXELS)
+fn rasterize_tiled(
+    framebuffer: &mut Framebuffer,
+    triangles: &[Triangle],
+    uniforms: &Uniforms,
+    fragment_shader: &dyn FragmentShader,
+    options: &DrawOptions,
+) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    let tiles_x = width.div_ceil(TILE_SIZE);
+    let tiles_y = height.div_ceil(TILE_SIZE);
+
+    let mut bins: Vec<Vec<usize>> = vec![Vec::new(); tiles_x * tiles_y];
+    let mut pixels = 0;
+    for (index, triangle) in triangles.iter().enumerate() {
+        if let Some(rect) = triangle.bounding_box(width, height) {
+            pixels += (rect.max_x - rect.min_x + 1) as usize * (rect.max_y - rect.min_y + 1) as usize;
+            for tile_y in rect.min_y as usize / TILE_SIZE..=rect.max_y as usize / TILE_SIZE {
+                for tile_x in rect.min_x as usize / TILE_SIZE..=rect.max_x as usize / TILE_SIZE {
+                    bins[tile_y * tiles_x + tile_x].push(index);
+                }
+            }
+        }
+    }
+
+    let fragment_color = framebuffer.current_color;
+
+    // Cada fila de tiles es un bloque contiguo del buffer HDR y de profundidad
+    let tile_rows: Vec<(usize, &mut [HdrColor], &mut [f32])> = framebuffer
+        .hdr_buffer
+        .chunks_mut(width * TILE_SIZE)
+        .zip(framebuffer.zbuffer.chunks_mut(width * TILE_SIZE))
+        .enumerate()
+        .filter(|(tile_y, _)| bins[tile_y * tiles_x..(tile_y + 1) * tiles_x].iter().any(|bin| !bin.is_empty()))
+        .map(|(tile_y, (colors, depths))| (tile_y, colors, depths))
+        .collect();
+
+    let threads = if pixels < PARALLEL_MIN_PIXELS {
+        1
+    } else {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(tile_rows.len())
+    };
+    let queue = Mutex::new(tile_rows.into_iter());
+
+    let work = || loop {
+        let job = queue.lock().unwrap().next();
+        let Some((tile_y, colors, depths)) = job else {
+            break;
+        };
+
+        let row_start = tile_y * TILE_SIZE;
+        for tile_x in 0..tiles_x {
+            let tile = PixelRect {
+                min_x: (tile_x * TILE_SIZE) as i32,
+                max_x: ((tile_x + 1) * TILE_SIZE).min(width) as i32 - 1,
+                min_y: row_start as i32,
+                max_y: (row_start + TILE_SIZE).min(height) as i32 - 1,
+            };
+
+            for &index in &bins[tile_y * tiles_x + tile_x] {
+                let triangle = &triangles[index];
+                let Some(rect) = triangle
+                    .bounding_box(width, height)
+                    .and_then(|bounds| bounds.intersect(&tile))
+                else {
+                    continue;
+                };
+
+                triangle.rasterize(&rect, |sample| {
+                    let local = (sample.y as usize - row_start) * width + sample.x as usize;
+
+                    // Early-z: los fragmentos ocultos no llegan al shader
+                    if options.depth_test && sample.depth >= depths[local] {
+                        return;
+                    }
+
+                    let fragment = triangle.fragment_at(&sample, fragment_color);
+                    let shaded = fragment_shader.shade(&fragment, uniforms);
+                    colors[local] = options.blend.blend(colors[local], shaded);
+                    if options.depth_write {
+                        depths[local] = sample.depth;
+                    }
+                });
+            }
+        }
+    };
+
+    if threads <= 1 {
+        work();
+        return;
+    }
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(work);
+        }
+    });
+}
+
+// Modelos y shaders compartidos entre la ventana y el modo headless
+pub struct SceneAssets {
+    pub planet_lod: LodChain,
+    pub shaders: ShaderRegistry,
+}
+
+impl SceneAssets {
+    // Esfera generada en código (con sus niveles de detalle) para planetas y
+    // lunas sin malla propia
+    pub fn new() -> Self {
+        SceneAssets {
+            planet_lod: LodChain::uv_sphere(SPHERE_RADIUS, DEFAULT_SPHERE_SEGMENTS, DEFAULT_SPHERE_RINGS),
+            shaders: ShaderRegistry::with_builtin_shaders(),
+        }
+    }
+}
+
+impl Default for SceneAssets {
+    fn default() -> Self {
+        SceneAssets::new()
+    }
+}
+
+fn frame_uniforms(framebuffer: &Framebuffer, scene: &Scene, camera: &Camera, time: f32) -> Uniforms {
+    let width = framebuffer.width as f32;
+    let height = framebuffer.height as f32;
+
+    Uniforms {
+        model_matrix: Mat4::identity(),
+        view_matrix: camera.get_view_matrix(),
+        projection_matrix: camera.get_projection_matrix(width / height),
+        viewport_matrix: create_viewport_matrix(width, height),
+        time,
+        lights: scene.lights_at(&scene.bodies, Vec3::zeros()),
+        camera_position: camera.eye,
+        occluders: analytic_occluders(scene.shadows, &scene.bodies),
+        // La esfera compartida sin escalar, como la matriz de modelo identidad
+        body_radius: SPHERE_RADIUS,
+    }
+}
+
+// Las esferas de `bodies` si la escena usa sombras analíticas
+fn analytic_occluders(method: ShadowMethod, bodies: &[Planet]) -> Occluders {
+    match method {
+        ShadowMethod::Analytic => occluders(bodies),
+        ShadowMethod::None | ShadowMethod::Map => Occluders::new(),
+    }
+}
+
+// Dibuja un planeta centrado (con sus lunas y anillos de la escena) en el framebuffer
+#[allow(clippy::too_many_arguments)]
+pub fn render_planet_view(
+    framebuffer: &mut Framebuffer,
+    assets: &SceneAssets,
+    lod: &mut LodState,
+    scene: &Scene,
+    camera: &Camera,
+    shader_type: &str,
+    rotation: Vec3,
+    time: f32,
+) -> Result<(), String> {
+    let mut planet = scene
+        .find_by_shader(shader_type)
+        .cloned()
+        .unwrap_or_else(|| Planet::new(shader_type, shader_type, 1.0, 0.0, 0.0));
+
+    // El planeta queda fijo en el origen; sus lunas siguen orbitando según `time`
+    planet.reset();
+    for moon in &mut planet.moons {
+        moon.update(time);
+    }
+    planet.rotation = rotation;
+    planet.scale = 1.0;
+
+    framebuffer.set_background_color(scene.background);
+    framebuffer.clear();
+
+    // El sol queda donde estaría respecto del planeta con las órbitas en `time`;
+    // solo el planeta y sus lunas, a la escala de esta vista, hacen sombra
+    let orbits = scene.orbits_at(time);
+    let focus = world_position(&orbits, &planet.name).unwrap_or(Vec3::zeros());
+    let uniforms = Uniforms {
+        lights: scene.lights_at(&orbits, focus),
+        occluders: analytic_occluders(scene.shadows, std::slice::from_ref(&planet)),
+        ..frame_uniforms(framebuffer, scene, camera, time)
+    };
+
+    let mut transparent = TransparentQueue::new();
+    lod.begin_frame();
+    render_body(framebuffer, assets, lod, &mut transparent, &planet, "", Vec3::new(0.0, 0.0, 0.0), 1.0, &uniforms, scene.shadows, None)?;
+    transparent.flush(framebuffer, assets.shaders.vertex(DEFAULT_VERTEX_SHADER)?);
+
+    scene.post_processing.apply(framebuffer);
+    framebuffer.resolve(&scene.tone_mapping);
+    if lod.show_overlay {
+        lod.draw_overlay(framebuffer);
+    }
+    Ok(())
+}
+
+// Dibuja todos los cuerpos de la escena en sus posiciones orbitales
+pub fn render_system_view(
+    framebuffer: &mut Framebuffer,
+    assets: &SceneAssets,
+    lod: &mut LodState,
+    scene: &Scene,
+    camera: &Camera,
+    time: f32,
+) -> Result<(), String> {
+    framebuffer.set_background_color(scene.background);
+    framebuffer.clear();
+
+    let uniforms = frame_uniforms(framebuffer, scene, camera, time);
+    let mut transparent = TransparentQueue::new();
+    lod.begin_frame();
+    for planet in &scene.bodies {
+        render_body(framebuffer, assets, lod, &mut transparent, planet, "", Vec3::new(0.0, 0.0, 0.0), 1.0, &uniforms, scene.shadows, None)?;
+    }
+    transparent.flush(framebuffer, assets.shaders.vertex(DEFAULT_VERTEX_SHADER)?);
+
+    scene.post_processing.apply(framebuffer);
+    framebuffer.resolve(&scene.tone_mapping);
+    if lod.show_overlay {
+        lod.draw_overlay(framebuffer);
+    }
+    Ok(())
+}
+
+// Dibuja un cuerpo y sus lunas; los anillos se encolan en `transparent` para
+// después de lo opaco. `origin` es la posición del padre y `parent_scale` su
+// escala final, que multiplica la órbita y el tamaño; `parent_key` identifica
+// al padre para recordar el nivel de detalle de cada cuerpo. Con sombras por
+// mapa, un cuerpo con lunas arma el de su sistema y lo hereda a sus lunas
+#[allow(clippy::too_many_arguments)]
+fn render_body<'a>(
+    framebuffer: &mut Framebuffer,
+    assets: &'a SceneAssets,
+    lod: &mut LodState,
+    transparent: &mut TransparentQueue<'a>,
+    planet: &'a Planet,
+    parent_key: &str,
+    origin: Vec3,
+    parent_scale: f32,
+    frame_uniforms: &Uniforms,
+    shadow_method: ShadowMethod,
+    shadow_map: Option<&ShadowMap>,
+) -> Result<(), String> {
+    let vertex_shader = assets.shaders.vertex(DEFAULT_VERTEX_SHADER)?;
+
+    let own_shadow_map = match shadow_map {
+        None if shadow_method == ShadowMethod::Map && !planet.moons.is_empty() => {
+            build_shadow_map(assets, planet, origin, parent_scale, &frame_uniforms.lights)
+        }
+        _ => None,
+    };
+    let shadow_map = shadow_map.or(own_shadow_map.as_ref());
+
+    let position = origin + planet.position * parent_scale;
+    let scale = planet.scale * parent_scale;
+
+    let chain = planet.lod.as_deref().unwrap_or(&assets.planet_lod);
+    let body_radius = scale * chain.bounding_radius();
+
+    let uniforms = Uniforms {
+        model_matrix: create_model_matrix(position, scale, planet.rotation),
+        body_radius,
+        ..*frame_uniforms
+    };
+
+    let ring_uniforms = planet.ring.as_ref().map(|ring| Uniforms {
+        model_matrix: create_model_matrix(position, scale, Vec3::new(ring.tilt, planet.rotation.y, 0.0)),
+        body_radius,
+        ..*frame_uniforms
+    });
+
+    let key = format!("{}/{}", parent_key, planet.name);
+    let model = lod.choose(&key, chain, position, scale, &uniforms);
+    let receiver = ShadowReceiver {
+        ring: planet.ring.as_ref().zip(ring_uniforms.as_ref()).map(|(ring, ring_uniforms)| ring.shadow(&ring_uniforms.model_matrix)),
+        shadow_map,
+    };
+    if planet.shader_type == MATERIAL_SHADER {
+        render_with_materials(framebuffer, &uniforms, model, vertex_shader, &DrawOptions::default(), &receiver);
+    } else {
+        let fragment_shader = assets.shaders.fragment(&planet.shader_type)?;
+        render(framebuffer, &uniforms, model, vertex_shader, &receiver.wrap(fragment_shader), &DrawOptions::default());
+    }
+
+    if let (Some(ring), Some(ring_uniforms)) = (&planet.ring, ring_uniforms) {
+        let ring_shader = assets.shaders.fragment("ring")?;
+        let options = DrawOptions::double_sided().translucent();
+        transparent.push(&ring.model, ring_shader, ring_uniforms, options, position);
+    }
+
+    // Cáscara de la atmósfera: suma la luz que dispersa sobre lo que hay
+    // detrás (el planeta o el fondo) ya atenuado
+    if let Some(atmosphere) = &planet.atmosphere {
+        let shell = Shell { center: position, radius: body_radius * atmosphere.radius, rotation: planet.rotation };
+        shell.push(transparent, assets, atmosphere, BlendMode::Premultiplied, frame_uniforms);
+    }
+    // Halo del cuerpo emisor: solo agrega luz encima de lo que ya está
+    if let Some(glow) = &planet.glow {
+        let shell = Shell { center: position, radius: body_radius * glow.radius, rotation: planet.rotation };
+        shell.push(transparent, assets, glow, BlendMode::Additive, frame_uniforms);
+    }
+
+    for moon in &planet.moons {
+        render_body(framebuffer, assets, lod, transparent, moon, &key, position, scale, frame_uniforms, shadow_method, shadow_map)?;
+    }
+
+    Ok(())
+}
+
+// Capa esférica alrededor de un cuerpo (atmósfera, halo): la esfera compartida
+// escalada para envolver el radio real del cuerpo, sea cual sea su malla
+struct Shell {
+    center: Vec3,
+    radius: f32,
+    rotation: Vec3,
+}
+
+impl Shell {
+    fn push<'a>(
+        &self,
+        transparent: &mut TransparentQueue<'a>,
+        assets: &'a SceneAssets,
+        shader: &'a dyn FragmentShader,
+        blend: BlendMode,
+        frame_uniforms: &Uniforms,
+    ) {
+        let uniforms = Uniforms {
+            model_matrix: create_model_matrix(self.center, self.radius / SPHERE_RADIUS, self.rotation),
+            ..*frame_uniforms
+        };
+        let options = DrawOptions { blend, depth_write: false, ..DrawOptions::default() };
+        // Desde afuera se dibujan las caras frontales, que otro cuerpo delante
+        // puede tapar. Desde adentro (o tan cerca que el plano cercano las
+        // recorta) solo quedan las traseras, detrás del cuerpo: se dibujan sin
+        // test de profundidad y el shader decide qué parte del rayo cuenta
+        let inside = (frame_uniforms.camera_position - self.center).magnitude() - self.radius < NEAR_PLANE;
+        let options = if inside {
+            DrawOptions { front_face: Winding::Clockwise, depth_test: false, ..options }
+        } else {
+            options
+        };
+        transparent.push(assets.planet_lod.finest(), shader, uniforms, options, self.center);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::default_camera;
+    use crate::color::Color;
+    use crate::light::{Light, Lights};
+    use crate::shaders::vertex_shader;
+    use nalgebra_glm::Vec2;
+    use std::sync::Arc;
+
+    const WIDTH: usize = 200;
+    const HEIGHT: usize = 150;
+
+    // Uniforms de la vista de planeta por defecto sobre WIDTH x HEIGHT
+    fn uniforms() -> Uniforms {
+        frame_uniforms(&Framebuffer::new(WIDTH, HEIGHT), &Scene::default(), &default_camera(false), 0.0)
+    }
+
+    // Un sol blanco puntual, sin radio
+    fn sun_at(position: Vec3) -> Lights {
+        Lights::single(Light::point(position, HdrColor::new(1.0, 1.0, 1.0), 1.0, 0.0))
+    }
+
+    // Dibuja `model` con el vertex shader por defecto sobre un framebuffer limpio
+    fn draw(background: HdrColor, uniforms: &Uniforms, model: &Model, shader: &dyn FragmentShader, options: &DrawOptions) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+        framebuffer.set_background_color(background);
+        framebuffer.clear();
+        render(&mut framebuffer, uniforms, model, &vertex_shader, shader, options);
+        framebuffer
+    }
+
+    // La vista de planeta completa, con fondo, capas translúcidas y post-proceso
+    fn planet_view(assets: &SceneAssets, scene: &Scene, camera: &Camera, focus: &str) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(80, 60);
+        render_planet_view(&mut framebuffer, assets, &mut LodState::new(), scene, camera, focus, Vec3::zeros(), 0.0).unwrap();
+        framebuffer
+    }
+
+    // Ruta original en un solo hilo: rasterizar todo, luego sombrear en orden
+    fn render_serial(
+        framebuffer: &mut Framebuffer,
+        uniforms: &Uniforms,
+        model: &Model,
+        fragment_shader: &dyn FragmentShader,
+        options: &DrawOptions,
+    ) {
+        // Sin compartir vértices: cada esquina de cada triángulo se transforma aparte
+        let mut triangles = Vec::new();
+        for corners in model.triangles() {
+            let transformed = corners.map(|v| vertex_shader(v, uniforms));
+            clip_triangle([&transformed[0], &transformed[1], &transformed[2]], &uniforms.viewport_matrix, &mut triangles);
+        }
+
+        if options.cull_back_faces {
+            triangles.retain(|triangle| triangle.is_front_facing(options.front_face));
+        }
+
+        let mut all_fragments = Vec::new();
+        for triangle in &triangles {
+            if let Some(rect) = triangle.bounding_box(framebuffer.width, framebuffer.height) {
+                let color = framebuffer.current_color;
+                triangle.rasterize(&rect, |sample| all_fragments.push((sample, triangle.fragment_at(&sample, color))));
+            }
+        }
+
+        for (sample, fragment) in all_fragments {
+            let index = sample.y as usize * framebuffer.width + sample.x as usize;
+            if options.depth_test && sample.depth >= framebuffer.zbuffer[index] {
+                continue;
+            }
+
+            let shaded = fragment_shader.shade(&fragment, uniforms);
+            framebuffer.hdr_buffer[index] = options.blend.blend(framebuffer.hdr_buffer[index], shaded);
+            if options.depth_write {
+                framebuffer.zbuffer[index] = sample.depth;
+            }
+        }
+    }
+
+    #[test]
+    fn tiled_output_matches_serial_path() {
+        let assets = SceneAssets::new();
+        let ring = Planet::new("Saturno", "gas_saturn", 1.0, 0.0, 0.0)
+            .with_ring(crate::planet::Ring::new(1.2, 1.8, 0.4));
+        let ring_model = &ring.ring.as_ref().unwrap().model;
+        let mut camera = default_camera(false);
+        camera.orbit(0.7, 0.3);
+
+        // Tamaño que no es múltiplo del tile para cubrir los bordes; el más
+        // chico queda bajo PARALLEL_MIN_PIXELS y se rasteriza sin hilos
+        for (width, height) in [(800, 600), (203, 117), (40, 30)] {
+            let mut tiled = Framebuffer::new(width, height);
+            let mut serial = Framebuffer::new(width, height);
+            tiled.clear();
+            serial.clear();
+
+            let scene = Scene::default();
+            let uniforms = frame_uniforms(&tiled, &scene, &camera, 1.5);
+            for (model, shader, options) in [
+                (assets.planet_lod.finest(), "gas_saturn", DrawOptions::default()),
+                (ring_model, "ring", DrawOptions::double_sided().translucent()),
+            ] {
+                let fragment_shader = assets.shaders.fragment(shader).unwrap();
+                render(&mut tiled, &uniforms, model, &vertex_shader, fragment_shader, &options);
+                render_serial(&mut serial, &uniforms, model, fragment_shader, &options);
+            }
+
+            assert!(tiled.hdr_buffer == serial.hdr_buffer, "color difiere en {}x{}", width, height);
+            assert!(tiled.zbuffer == serial.zbuffer, "profundidad difiere en {}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn early_z_skips_hidden_fragments() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let assets = SceneAssets::new();
+        let uniforms = uniforms();
+        let shaded = AtomicUsize::new(0);
+        let counting_shader = |_: &crate::fragment::Fragment, _: &Uniforms| {
+            shaded.fetch_add(1, Ordering::Relaxed);
+            Color::new(255, 0, 0)
+        };
+
+        let mut framebuffer = draw(HdrColor::black(), &uniforms, assets.planet_lod.finest(), &counting_shader, &DrawOptions::default());
+        let first_pass = shaded.swap(0, Ordering::Relaxed);
+        assert!(first_pass > 0);
+
+        // La misma esfera otra vez: toda su profundidad ya está en el z-buffer
+        render(&mut framebuffer, &uniforms, assets.planet_lod.finest(), &vertex_shader, &counting_shader, &DrawOptions::default());
+        assert_eq!(shaded.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn translucent_rings_blend_over_what_is_behind() {
+        let assets = SceneAssets::new();
+        let saturn = Planet::new("Saturno", "gas_saturn", 1.0, 0.0, 0.0)
+            .with_ring(crate::planet::Ring::new(1.2, 1.8, 0.4));
+        let ring_model = &saturn.ring.as_ref().unwrap().model;
+        let ring_shader = assets.shaders.fragment("ring").unwrap();
+        let uniforms = Uniforms {
+            model_matrix: create_model_matrix(Vec3::new(0.0, 0.0, 0.0), 1.0, Vec3::new(0.4, 0.0, 0.0)),
+            ..uniforms()
+        };
+        let options = DrawOptions::double_sided().translucent();
+
+        let over_black = draw(HdrColor::black(), &uniforms, ring_model, ring_shader, &options);
+        let over_blue = draw(HdrColor::new(0.0, 0.0, 1.0), &uniforms, ring_model, ring_shader, &options);
+
+        // El anillo cubre píxeles pero no escribe profundidad, y lo que hay
+        // detrás se sigue viendo a través de él
+        let covered: Vec<usize> = (0..over_black.hdr_buffer.len())
+            .filter(|&i| over_black.hdr_buffer[i] != HdrColor::black())
+            .collect();
+        assert!(!covered.is_empty());
+        assert!(over_black.zbuffer.iter().all(|depth| depth.is_infinite()));
+        assert!(covered.iter().all(|&i| over_blue.hdr_buffer[i].b > over_black.hdr_buffer[i].b));
+        assert!(covered.iter().all(|&i| over_blue.hdr_buffer[i].b < 1.0));
+    }
+
+    #[test]
+    fn shared_edges_blend_a_single_layer() {
+        // Abanico de triángulos con aristas horizontales, verticales y
+        // diagonales que pasan justo por centros de píxel, en ambos sentidos de giro
+        let size = 40.0;
+        let at = |x: f32, y: f32| Vertex::new(Vec3::new(x / size * 2.0 - 1.0, 1.0 - y / size * 2.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec2::zeros());
+        let rim = [(4.5, 4.5), (20.5, 2.5), (36.5, 4.5), (38.5, 20.5), (36.5, 36.5), (20.5, 38.5), (4.5, 36.5), (2.5, 20.5)];
+        let mut vertices = vec![at(20.5, 20.5)];
+        vertices.extend(rim.iter().map(|&(x, y)| at(x, y)));
+        let indices: Vec<u32> = (0..rim.len() as u32)
+            .flat_map(|i| {
+                let (a, b) = (1 + i, 1 + (i + 1) % rim.len() as u32);
+                if i % 2 == 0 { [0, a, b] } else { [0, b, a] }
+            })
+            .collect();
+        let fan = Model::new(vertices, indices);
+
+        let mut framebuffer = Framebuffer::new(size as usize, size as usize);
+        framebuffer.clear();
+        let uniforms = Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: create_viewport_matrix(size, size),
+            time: 0.0,
+            lights: Lights::default(),
+            camera_position: Vec3::zeros(),
+            occluders: Occluders::new(),
+            body_radius: 1.0,
+        };
+        let half_white = |_: &Fragment, _: &Uniforms| Rgba::new(HdrColor::new(1.0, 1.0, 1.0), 0.5);
+        let options = DrawOptions::double_sided().translucent();
+        render(&mut framebuffer, &uniforms, &fan, &vertex_shader, &half_white, &options);
+
+        // Cada píxel queda sin tocar o con una sola capa: ni costuras oscuras
+        // (huecos) ni claras (mezclado dos veces)
+        let single_layer = HdrColor::black().lerp(&HdrColor::new(1.0, 1.0, 1.0), 0.5);
+        assert!(framebuffer.hdr_buffer.iter().all(|&color| color == HdrColor::black() || color == single_layer));
+        for (x, y) in [(20, 20), (20, 10), (10, 20), (10, 10), (30, 30), (12, 28)] {
+            assert_eq!(framebuffer.hdr_buffer[y * size as usize + x], single_layer, "píxel ({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn planets_are_lit_from_the_sun_position() {
+        let assets = SceneAssets::new();
+        let shader = assets.shaders.fragment("ice_neptune").unwrap();
+
+        // Sol a la izquierda o a la derecha del planeta, a la misma distancia
+        let lit_from = |sun_x: f32| {
+            let uniforms = Uniforms { lights: sun_at(Vec3::new(sun_x, 0.0, 0.0)), ..uniforms() };
+            draw(HdrColor::black(), &uniforms, assets.planet_lod.finest(), shader, &DrawOptions::default())
+        };
+        let brightness = |framebuffer: &Framebuffer, columns: std::ops::Range<usize>| -> f32 {
+            columns.map(|x| framebuffer.hdr_buffer[HEIGHT / 2 * WIDTH + x].luminance()).sum()
+        };
+
+        let lit_from_left = lit_from(-10.0);
+        let lit_from_right = lit_from(10.0);
+        assert!(brightness(&lit_from_left, 60..100) > brightness(&lit_from_left, 100..140));
+        assert!(brightness(&lit_from_right, 100..140) > brightness(&lit_from_right, 60..100));
+    }
+
+    #[test]
+    fn moons_eclipse_the_planet_through_the_shadow_map() {
+        let assets = SceneAssets::new();
+        let shader = assets.shaders.fragment("ice_neptune").unwrap();
+
+        // Una luna entre el sol y la cara iluminada del planeta
+        let mut shadow_map = ShadowMap::new(256);
+        shadow_map.fit(Vec3::zeros(), 2.5, Vec3::new(1.0, 0.0, 0.0));
+        let moon_matrix = create_model_matrix(Vec3::new(1.8, 0.0, 0.0), 0.6, Vec3::zeros());
+        shadow_map.render_depth(assets.planet_lod.finest(), &moon_matrix);
+
+        let uniforms = Uniforms {
+            lights: sun_at(Vec3::new(10.0, 0.0, 0.0)),
+            occluders: Occluders::new(),
+            ..uniforms()
+        };
+        let receive = |receiver: ShadowReceiver| {
+            draw(HdrColor::black(), &uniforms, assets.planet_lod.finest(), &receiver.wrap(shader), &DrawOptions::default())
+        };
+        let brightness = |framebuffer: &Framebuffer, x: usize, y: usize| framebuffer.hdr_buffer[y * WIDTH + x].luminance();
+
+        let lit = receive(ShadowReceiver::default());
+        let eclipsed = receive(ShadowReceiver { ring: None, shadow_map: Some(&shadow_map) });
+
+        // La sombra cae en el centro de la cara iluminada, no cerca del polo
+        assert!(brightness(&eclipsed, 130, 75) < brightness(&lit, 130, 75) * 0.5);
+        assert_eq!(brightness(&eclipsed, 118, 50), brightness(&lit, 118, 50));
+    }
+
+    #[test]
+    fn atmospheres_wrap_the_body_and_show_from_inside() {
+        let assets = SceneAssets::new();
+        let scene = Scene::default();
+        let mut without = Scene::default();
+        for body in &mut without.bodies {
+            body.atmosphere = None;
+        }
+
+        let draw = |scene: &Scene, eye: Vec3| {
+            let mut camera = default_camera(false);
+            camera.eye = eye;
+            planet_view(&assets, scene, &camera, "rocky_earth").hdr_buffer
+        };
+        let hazed = |scene: &Scene, bare: &Scene, eye: Vec3| {
+            let (with, without) = (draw(scene, eye), draw(bare, eye));
+            with.iter().zip(&without).filter(|(a, b)| a != b).count()
+        };
+
+        // Desde adentro de la cáscara (radio 1.15 veces el del planeta) sus
+        // caras frontales quedan detrás de la cámara, pero el aire se ve igual
+        let inside = Vec3::new(0.0, 0.0, SPHERE_RADIUS * 1.1);
+        assert_eq!(hazed(&scene, &without, inside), 80 * 60);
+
+        // Con una malla más grande que la esfera compartida la cáscara la
+        // envuelve en lugar de quedar escondida adentro
+        let grow = |scene: &mut Scene| {
+            for body in &mut scene.bodies {
+                body.lod = Some(Arc::new(LodChain::icosphere(SPHERE_RADIUS * 2.0, 3)));
+            }
+        };
+        let (mut big, mut big_without) = (Scene::default(), Scene::default());
+        grow(&mut big);
+        grow(&mut big_without);
+        for body in &mut big_without.bodies {
+            body.atmosphere = None;
+        }
+        assert!(hazed(&big, &big_without, Vec3::new(0.0, 0.0, 4.5)) > 400);
+    }
+
+    #[test]
+    fn sun_glow_only_adds_light() {
+        let assets = SceneAssets::new();
+        let draw = |glow: bool| {
+            let mut scene = Scene::default();
+            scene.post_processing.bloom.enabled = false;
+            if !glow {
+                scene.bodies[0].glow = None;
+            }
+            planet_view(&assets, &scene, &default_camera(false), "sun")
+        };
+        let (with, without) = (draw(true), draw(false));
+
+        // El halo no escribe profundidad ni oscurece nada: cada canal queda
+        // igual o más brillante, y alrededor del disco aparece luz sobre el fondo
+        assert_eq!(with.zbuffer, without.zbuffer);
+        for (a, b) in with.hdr_buffer.iter().zip(&without.hdr_buffer) {
+            assert!(a.r >= b.r && a.g >= b.g && a.b >= b.b);
+        }
+        let lit_background = (0..with.hdr_buffer.len())
+            .filter(|&i| without.zbuffer[i].is_infinite() && with.hdr_buffer[i].luminance() > without.hdr_buffer[i].luminance() + 0.01)
+            .count();
+        assert!(lit_background > 100, "solo {} píxeles de fondo con halo", lit_background);
+    }
+
+    #[test]
+    fn blend_modes_combine_with_the_destination() {
+        let destination = HdrColor::new(0.2, 0.4, 0.6);
+        let source = Rgba::new(HdrColor::new(1.0, 1.0, 1.0), 0.25);
+
+        assert_eq!(BlendMode::Opaque.blend(destination, source), source.color);
+        let alpha = BlendMode::Alpha.blend(destination, source);
+        assert!((alpha.r - 0.4).abs() < 1e-6 && (alpha.b - 0.7).abs() < 1e-6);
+        let additive = BlendMode::Additive.blend(destination, source);
+        assert!((additive.g - 0.65).abs() < 1e-6);
+        let premultiplied = BlendMode::Premultiplied.blend(destination, source);
+        assert!((premultiplied.r - 1.15).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,1120 @@
+use crate::color::{Color, HdrColor};
+use crate::material::MATERIAL_SHADER;
+use crate::obj_loader::{Model, ParseMode};
+use crate::primitives::SPHERE_RADIUS;
+use crate::lod::LodChain;
+use crate::planet::{create_solar_system, Planet, Ring};
+use crate::shader_registry::ShaderRegistry;
+use crate::texture::{Filter, Sampler, Texture, TexturedShader, WrapMode};
+use crate::tonemap::{ToneMapping, ToneOperator};
+use crate::postprocess::PostProcessing;
+use crate::light::{Light, LightSource, Lights, MAX_LIGHTS};
+use crate::occlusion::{Occluder, Occluders};
+use crate::shadow::ShadowMethod;
+use crate::atmosphere::Atmosphere;
+use crate::glow::Glow;
+
+use nalgebra_glm::Vec3;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const DEFAULT_SCENE_PATH: &str = "scenes/sistema_solar.toml";
+
+// Pseudo-shader de la escena: requiere `texture` y registra un TexturedShader por cuerpo
+const TEXTURED_SHADER: &str = "textured";
+
+// Más allá de esto el desenfoque cuesta más que todo el frame
+pub const MAX_BLOOM_RADIUS: usize = 128;
+
+// `background` se escribe en sRGB de 8 bits y se usa como radiancia
+const DEFAULT_BACKGROUND: Color = Color { r: 10, g: 5, b: 20 };
+
+// Luz de la escena; una puntual con `body` sigue a ese cuerpo (el sol) y su
+// posición se toma del cuerpo en cada frame, igual que su radio si no lo declara
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneLight {
+    pub light: Light,
+    pub body: Option<String>,
+}
+
+pub struct Scene {
+    // Radiancia del fondo, lineal como la de los shaders
+    pub background: HdrColor,
+    pub lights: Vec<SceneLight>,
+    pub tone_mapping: ToneMapping,
+    pub post_processing: PostProcessing,
+    pub shadows: ShadowMethod,
+    pub bodies: Vec<Planet>,
+    // Problemas de los OBJ cargados con `strict = false`
+    pub warnings: Vec<String>,
+}
+
+// ============================================
+// FORMATO DEL ARCHIVO (TOML)
+// ============================================
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    background: Option<[u8; 3]>,
+    light: Option<LightFile>,
+    #[serde(default)]
+    lights: Vec<SceneLightFile>,
+    tone_mapping: Option<ToneMappingFile>,
+    bloom: Option<BloomFile>,
+    shadows: Option<ShadowsFile>,
+    #[serde(default)]
+    bodies: Vec<BodyFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LightFile {
+    direction: [f32; 3],
+}
+
+// `[[lights]]`: `type = "point"` con `position` o `body`, o `type = "directional"`
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum SceneLightFile {
+    Point {
+        position: Option<[f32; 3]>,
+        body: Option<String>,
+        color: Option<[u8; 3]>,
+        intensity: Option<f32>,
+        #[serde(default)]
+        falloff: f32,
+        #[serde(default)]
+        radius: f32,
+    },
+    Directional {
+        direction: [f32; 3],
+        color: Option<[u8; 3]>,
+        intensity: Option<f32>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToneMappingFile {
+    operator: Option<String>,
+    exposure: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BloomFile {
+    enabled: Option<bool>,
+    threshold: Option<f32>,
+    intensity: Option<f32>,
+    radius: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShadowsFile {
+    method: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BodyFile {
+    name: String,
+    shader: String,
+    scale: f32,
+    parent: Option<String>,
+    #[serde(default)]
+    orbit_radius: f32,
+    #[serde(default)]
+    orbit_speed: f32,
+    rotation_speed: Option<[f32; 3]>,
+    ring: Option<RingFile>,
+    atmosphere: Option<AtmosphereFile>,
+    glow: Option<GlowFile>,
+    texture: Option<String>,
+    model: Option<String>,
+    strict: Option<bool>,
+    normals: Option<String>,
+    mesh: Option<MeshFile>,
+    filter: Option<String>,
+    wrap: Option<String>,
+    wrap_u: Option<String>,
+    wrap_v: Option<String>,
+}
+
+// Malla generada en lugar de la esfera compartida, p. ej.
+// `mesh = { type = "icosphere", subdivisions = 4 }`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MeshFile {
+    UvSphere {
+        #[serde(default = "default_segments")]
+        segments: u32,
+        #[serde(default = "default_rings")]
+        rings: u32,
+    },
+    Icosphere {
+        #[serde(default = "default_subdivisions")]
+        subdivisions: u32,
+    },
+    Torus {
+        #[serde(default = "default_major_radius")]
+        major_radius: f32,
+        #[serde(default = "default_minor_radius")]
+        minor_radius: f32,
+        #[serde(default = "default_segments")]
+        segments: u32,
+        #[serde(default = "default_sides")]
+        sides: u32,
+    },
+}
+
+fn default_segments() -> u32 { 48 }
+fn default_rings() -> u32 { 24 }
+fn default_subdivisions() -> u32 { 3 }
+fn default_major_radius() -> f32 { 0.5 }
+fn default_minor_radius() -> f32 { 0.2 }
+fn default_sides() -> u32 { 16 }
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RingFile {
+    inner_radius: f32,
+    outer_radius: f32,
+    #[serde(default)]
+    tilt: f32,
+}
+
+// Halo de un cuerpo emisor; lo que no se declare toma el del sol
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GlowFile {
+    radius: Option<f32>,
+    color: Option<[u8; 3]>,
+    intensity: Option<f32>,
+}
+
+// Cáscara de aire en unidades del radio del planeta; lo que no se declare
+// toma el valor de `preset` ("earth" o "neptune", por defecto "earth")
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AtmosphereFile {
+    preset: Option<String>,
+    radius: Option<f32>,
+    rayleigh: Option<[f32; 3]>,
+    rayleigh_scale_height: Option<f32>,
+    mie: Option<f32>,
+    mie_scale_height: Option<f32>,
+    mie_g: Option<f32>,
+    intensity: Option<f32>,
+}
+
+impl Scene {
+    pub fn load_from_file(path: &str, shaders: &mut ShaderRegistry) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Error abriendo escena {}: {}", path, e))?;
+
+        // Las rutas de texturas son relativas al archivo de escena
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new("."));
+
+        Scene::parse(&contents, base_dir, shaders).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    // Usa la escena indicada; sin ruta intenta la escena por defecto y si no
+    // existe cae al sistema solar integrado
+    pub fn load_or_default(path: Option<&str>, shaders: &mut ShaderRegistry) -> Result<Self, String> {
+        match path {
+            Some(path) => Scene::load_from_file(path, shaders),
+            None if std::path::Path::new(DEFAULT_SCENE_PATH).exists() => {
+                Scene::load_from_file(DEFAULT_SCENE_PATH, shaders)
+            }
+            None => Ok(Scene::default()),
+        }
+    }
+
+    pub fn parse(contents: &str, base_dir: &Path, shaders: &mut ShaderRegistry) -> Result<Self, String> {
+        let file: SceneFile = toml::from_str(contents)
+            .map_err(|e| format!("TOML inválido: {}", e))?;
+
+        let background = file
+            .background
+            .map(|[r, g, b]| HdrColor::from(Color::new(r, g, b)))
+            .unwrap_or(HdrColor::from(DEFAULT_BACKGROUND));
+
+        let mut lights = Vec::new();
+        if let Some(light) = file.light {
+            let direction = Vec3::new(light.direction[0], light.direction[1], light.direction[2]);
+            if !is_finite_vec(&direction) || direction.magnitude() == 0.0 {
+                return Err("light.direction debe ser un vector finito distinto de cero".to_string());
+            }
+            lights.push(SceneLight { light: Light::directional(direction, HdrColor::new(1.0, 1.0, 1.0), 1.0), body: None });
+        }
+        for light in &file.lights {
+            lights.push(parse_light(light, &file.bodies)?);
+        }
+        if lights.len() > MAX_LIGHTS {
+            return Err(format!("La escena declara {} luces; se admiten como máximo {}", lights.len(), MAX_LIGHTS));
+        }
+        // Sin luces declaradas se conserva la luz fija de siempre
+        if lights.is_empty() {
+            lights.push(SceneLight { light: Light::default(), body: None });
+        }
+
+        let mut tone_mapping = ToneMapping::default();
+        if let Some(section) = file.tone_mapping {
+            if let Some(operator) = section.operator {
+                tone_mapping.operator = ToneOperator::parse(&operator)?;
+            }
+            if let Some(exposure) = section.exposure {
+                if !exposure.is_finite() || exposure <= 0.0 {
+                    return Err(format!("tone_mapping.exposure debe ser positivo (se obtuvo {})", exposure));
+                }
+                tone_mapping.exposure = exposure;
+            }
+        }
+
+        let mut post_processing = PostProcessing::default();
+        if let Some(section) = file.bloom {
+            let bloom = &mut post_processing.bloom;
+            bloom.enabled = section.enabled.unwrap_or(bloom.enabled);
+            bloom.threshold = section.threshold.unwrap_or(bloom.threshold);
+            bloom.intensity = section.intensity.unwrap_or(bloom.intensity);
+            bloom.radius = section.radius.unwrap_or(bloom.radius);
+
+            if !bloom.threshold.is_finite() || bloom.threshold < 0.0 {
+                return Err(format!("bloom.threshold no puede ser negativo (se obtuvo {})", bloom.threshold));
+            }
+            if !bloom.intensity.is_finite() || bloom.intensity < 0.0 {
+                return Err(format!("bloom.intensity no puede ser negativa (se obtuvo {})", bloom.intensity));
+            }
+            if bloom.radius > MAX_BLOOM_RADIUS {
+                return Err(format!("bloom.radius admite hasta {} píxeles (se obtuvo {})", MAX_BLOOM_RADIUS, bloom.radius));
+            }
+        }
+
+        let shadows = match file.shadows.and_then(|section| section.method) {
+            Some(method) => ShadowMethod::parse(&method)?,
+            None => ShadowMethod::default(),
+        };
+
+        register_textures(&file.bodies, base_dir, shaders)?;
+        let mut warnings = Vec::new();
+        let models = load_models(&file.bodies, base_dir, &mut warnings)?;
+        let bodies = build_hierarchy(&file.bodies, &models, shaders)?;
+
+        Ok(Scene { background, lights, tone_mapping, post_processing, shadows, bodies, warnings })
+    }
+
+    // Busca (en cualquier nivel) el primer cuerpo que usa el shader dado
+    pub fn find_by_shader(&self, shader_type: &str) -> Option<&Planet> {
+        fn search<'a>(bodies: &'a [Planet], shader_type: &str) -> Option<&'a Planet> {
+            bodies.iter().find_map(|body| {
+                if body.shader_type == shader_type {
+                    Some(body)
+                } else {
+                    search(&body.moons, shader_type)
+                }
+            })
+        }
+
+        search(&self.bodies, shader_type)
+    }
+
+    // Luces del frame con sus cuerpos ubicados según `bodies` y las posiciones
+    // expresadas desde `origin`
+    pub fn lights_at(&self, bodies: &[Planet], origin: Vec3) -> Lights {
+        self.lights
+            .iter()
+            .map(|scene_light| {
+                let mut light = scene_light.light;
+                if let (Some(name), LightSource::Point { position, .. }) = (&scene_light.body, &mut light.source) {
+                    if let Some((body, body_position, scale)) = find_placed(bodies, name) {
+                        *position = body_position;
+                        if light.radius == 0.0 {
+                            light.radius = body_radius(body) * scale;
+                        }
+                    }
+                }
+                light.relative_to(origin)
+            })
+            .collect()
+    }
+
+    // Los cuerpos con las órbitas recorridas desde t = 0 hasta `time`
+    pub fn orbits_at(&self, time: f32) -> Vec<Planet> {
+        let mut bodies = self.bodies.clone();
+        for body in &mut bodies {
+            body.reset();
+            body.update(time);
+        }
+        bodies
+    }
+
+    pub fn reset(&mut self) {
+        for body in &mut self.bodies {
+            body.reset();
+        }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        for body in &mut self.bodies {
+            body.update(delta_time);
+        }
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene {
+            background: HdrColor::from(DEFAULT_BACKGROUND),
+            lights: vec![SceneLight {
+                light: Light::point(Vec3::zeros(), HdrColor::new(1.0, 1.0, 1.0), 1.0, 0.0),
+                body: Some("Sol".to_string()),
+            }],
+            tone_mapping: ToneMapping::default(),
+            post_processing: PostProcessing::default(),
+            shadows: ShadowMethod::default(),
+            bodies: create_solar_system(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+// ============================================
+// VALIDACIÓN Y CONSTRUCCIÓN DE LA JERARQUÍA
+// ============================================
+
+// Carga la textura de cada cuerpo con `shader = "textured"` y la registra
+// como un shader propio llamado `textured:<nombre del cuerpo>`
+fn register_textures(bodies: &[BodyFile], base_dir: &Path, shaders: &mut ShaderRegistry) -> Result<(), String> {
+    for body in bodies {
+        let texture_path = match (&body.texture, body.shader == TEXTURED_SHADER) {
+            (Some(path), true) => path,
+            (None, true) => {
+                return Err(format!("Cuerpo '{}': shader = \"textured\" requiere `texture`", body.name));
+            }
+            (Some(_), false) => {
+                return Err(format!("Cuerpo '{}': `texture` solo aplica con shader = \"textured\"", body.name));
+            }
+            (None, false) => continue,
+        };
+
+        let filter = match body.filter.as_deref() {
+            None | Some("bilinear") => Filter::Bilinear,
+            Some("nearest") => Filter::Nearest,
+            Some(other) => {
+                return Err(format!("Cuerpo '{}': filter desconocido '{}' (usa nearest o bilinear)", body.name, other));
+            }
+        };
+
+        // `wrap` fija ambos ejes; `wrap_u` y `wrap_v` lo ajustan por eje
+        let mut sampler = match body.wrap.as_deref() {
+            None => Sampler { filter, ..Sampler::default() },
+            Some(name) => Sampler::new(filter, parse_wrap(&body.name, "wrap", name)?),
+        };
+        if let Some(name) = body.wrap_u.as_deref() {
+            sampler.wrap_u = parse_wrap(&body.name, "wrap_u", name)?;
+        }
+        if let Some(name) = body.wrap_v.as_deref() {
+            sampler.wrap_v = parse_wrap(&body.name, "wrap_v", name)?;
+        }
+
+        let path = base_dir.join(texture_path);
+        let texture = Texture::load_from_file(&path.to_string_lossy())
+            .map_err(|e| format!("Cuerpo '{}': {}", body.name, e))?;
+
+        shaders.register_fragment(&shader_name(body), TexturedShader::new(texture, sampler))?;
+    }
+
+    Ok(())
+}
+
+fn parse_wrap(body: &str, field: &str, name: &str) -> Result<WrapMode, String> {
+    match name {
+        "repeat" => Ok(WrapMode::Repeat),
+        "mirror" => Ok(WrapMode::MirroredRepeat),
+        "clamp" => Ok(WrapMode::ClampToEdge),
+        other => Err(format!("Cuerpo '{}': {} desconocido '{}' (usa repeat, mirror o clamp)", body, field, other)),
+    }
+}
+
+// Malla propia de cada cuerpo (None: esfera compartida). Los OBJ se escalan
+// como la esfera para que `scale` signifique lo mismo y tienen un solo nivel;
+// las mallas generadas traen sus niveles de detalle. Archivos y mallas
+// repetidos se comparten entre cuerpos. Con `strict = false` los problemas
+// del OBJ se agregan a `warnings` en lugar de abortar la carga; `normals`
+// recalcula las normales del archivo ("smooth" o "flat")
+fn load_models(
+    bodies: &[BodyFile],
+    base_dir: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Option<Arc<LodChain>>>, String> {
+    let mut files: HashMap<(&str, ParseMode, Option<&str>), Arc<LodChain>> = HashMap::new();
+    let mut generated: Vec<(&MeshFile, Arc<LodChain>)> = Vec::new();
+
+    bodies
+        .iter()
+        .map(|body| match (&body.model, &body.mesh) {
+            (Some(path), _) => {
+                let mode = if body.strict == Some(false) { ParseMode::Lenient } else { ParseMode::Strict };
+                let normals = match body.normals.as_deref() {
+                    None | Some("file") => None,
+                    Some(kind @ ("smooth" | "flat")) => Some(kind),
+                    Some(other) => {
+                        return Err(format!("Cuerpo '{}': normals desconocido '{}' (usa file, smooth o flat)", body.name, other));
+                    }
+                };
+                if let Some(lod) = files.get(&(path.as_str(), mode, normals)) {
+                    return Ok(Some(Arc::clone(lod)));
+                }
+
+                let file = base_dir.join(path);
+                let (mut model, problems) = Model::load_with_mode(&file.to_string_lossy(), mode)
+                    .map_err(|e| format!("Cuerpo '{}': {}", body.name, e))?;
+                warnings.extend(problems.into_iter().map(|problem| format!("Cuerpo '{}': {}", body.name, problem)));
+                match normals {
+                    Some("smooth") => {
+                        model.generate_smooth_normals();
+                        model.generate_tangents();
+                    }
+                    Some("flat") => {
+                        model.generate_flat_normals();
+                        model.generate_tangents();
+                    }
+                    _ => {}
+                }
+                model.normalize_and_center(1.5);
+
+                let lod = Arc::new(LodChain::single(model));
+                files.insert((path, mode, normals), Arc::clone(&lod));
+                Ok(Some(lod))
+            }
+            (None, Some(mesh)) => {
+                if let Some((_, lod)) = generated.iter().find(|(spec, _)| *spec == mesh) {
+                    return Ok(Some(Arc::clone(lod)));
+                }
+
+                let lod = Arc::new(generate_mesh(mesh));
+                generated.push((mesh, Arc::clone(&lod)));
+                Ok(Some(lod))
+            }
+            (None, None) => Ok(None),
+        })
+        .collect()
+}
+
+fn generate_mesh(mesh: &MeshFile) -> LodChain {
+    match *mesh {
+        MeshFile::UvSphere { segments, rings } => LodChain::uv_sphere(SPHERE_RADIUS, segments, rings),
+        MeshFile::Icosphere { subdivisions } => LodChain::icosphere(SPHERE_RADIUS, subdivisions),
+        MeshFile::Torus { major_radius, minor_radius, segments, sides } => {
+            LodChain::torus(major_radius, minor_radius, segments, sides)
+        }
+    }
+}
+
+// Nombre en el registro del shader que usa un cuerpo
+fn shader_name(body: &BodyFile) -> String {
+    if body.shader == TEXTURED_SHADER {
+        format!("{}:{}", TEXTURED_SHADER, body.name)
+    } else {
+        body.shader.clone()
+    }
+}
+
+// Capas esféricas de un cuerpo ya validadas
+struct Shells {
+    atmosphere: Option<Atmosphere>,
+    glow: Option<Glow>,
+}
+
+fn parse_shells(body: &BodyFile) -> Result<Shells, String> {
+    Ok(Shells {
+        atmosphere: body.atmosphere.as_ref().map(parse_atmosphere).transpose()?,
+        glow: body.glow.as_ref().map(parse_glow).transpose()?,
+    })
+}
+
+fn build_hierarchy(
+    bodies: &[BodyFile],
+    models: &[Option<Arc<LodChain>>],
+    shaders: &ShaderRegistry,
+) -> Result<Vec<Planet>, String> {
+    let mut indices = HashMap::new();
+    let mut shells = Vec::with_capacity(bodies.len());
+    for (i, body) in bodies.iter().enumerate() {
+        validate_body(body, shaders)?;
+        shells.push(parse_shells(body).map_err(|e| format!("Cuerpo '{}': {}", body.name, e))?);
+        if indices.insert(body.name.as_str(), i).is_some() {
+            return Err(format!("Cuerpo duplicado: '{}'", body.name));
+        }
+    }
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); bodies.len()];
+    let mut roots = Vec::new();
+
+    for (i, body) in bodies.iter().enumerate() {
+        match &body.parent {
+            Some(parent) => {
+                let parent_index = *indices.get(parent.as_str()).ok_or_else(|| {
+                    format!("Cuerpo '{}': el padre '{}' no existe", body.name, parent)
+                })?;
+                if parent_index == i {
+                    return Err(format!("Cuerpo '{}': no puede ser su propio padre", body.name));
+                }
+                children[parent_index].push(i);
+            }
+            None => roots.push(i),
+        }
+    }
+
+    let mut visited = vec![false; bodies.len()];
+    let planets = roots
+        .iter()
+        .map(|&i| build_body(bodies, &children, models, &shells, i, &mut visited))
+        .collect();
+
+    // Un cuerpo no alcanzado desde la raíz forma parte de un ciclo de padres
+    if let Some(i) = visited.iter().position(|v| !v) {
+        return Err(format!("Cuerpo '{}': ciclo en la jerarquía de padres", bodies[i].name));
+    }
+
+    Ok(planets)
+}
+
+fn build_body(
+    bodies: &[BodyFile],
+    children: &[Vec<usize>],
+    models: &[Option<Arc<LodChain>>],
+    shells: &[Shells],
+    index: usize,
+    visited: &mut [bool],
+) -> Planet {
+    visited[index] = true;
+    let body = &bodies[index];
+
+    let mut planet = Planet::new(&body.name, &shader_name(body), body.scale, body.orbit_radius, body.orbit_speed);
+    if let Some([x, y, z]) = body.rotation_speed {
+        planet = planet.with_rotation_speed(Vec3::new(x, y, z));
+    }
+    if let Some(ring) = &body.ring {
+        planet = planet.with_ring(Ring::new(ring.inner_radius, ring.outer_radius, ring.tilt));
+    }
+    if let Some(atmosphere) = shells[index].atmosphere {
+        planet = planet.with_atmosphere(atmosphere);
+    }
+    if let Some(glow) = shells[index].glow {
+        planet = planet.with_glow(glow);
+    }
+    if let Some(lod) = &models[index] {
+        planet = planet.with_lod(Arc::clone(lod));
+    }
+
+    for &child in &children[index] {
+        planet = planet.with_moon(build_body(bodies, children, models, shells, child, visited));
+    }
+
+    planet
+}
+
+fn validate_body(body: &BodyFile, shaders: &ShaderRegistry) -> Result<(), String> {
+    let name = &body.name;
+
+    if name.trim().is_empty() {
+        return Err("Todos los cuerpos necesitan un nombre".to_string());
+    }
+    if body.shader == MATERIAL_SHADER {
+        if body.model.is_none() {
+            return Err(format!("Cuerpo '{}': shader = \"material\" requiere `model`", name));
+        }
+    } else if let Err(e) = shaders.fragment(&shader_name(body)) {
+        return Err(format!("Cuerpo '{}': {}", name, e));
+    }
+    if body.model.is_some() && body.mesh.is_some() {
+        return Err(format!("Cuerpo '{}': usa `model` o `mesh`, no ambos", name));
+    }
+    if body.strict.is_some() && body.model.is_none() {
+        return Err(format!("Cuerpo '{}': `strict` solo aplica con `model`", name));
+    }
+    if body.normals.is_some() && body.model.is_none() {
+        return Err(format!("Cuerpo '{}': `normals` solo aplica con `model`", name));
+    }
+    if let Some(mesh) = &body.mesh {
+        validate_mesh(mesh).map_err(|e| format!("Cuerpo '{}': {}", name, e))?;
+    }
+    if !body.scale.is_finite() || body.scale <= 0.0 {
+        return Err(format!("Cuerpo '{}': scale debe ser mayor que 0 (se obtuvo {})", name, body.scale));
+    }
+    if !body.orbit_radius.is_finite() || body.orbit_radius < 0.0 {
+        return Err(format!("Cuerpo '{}': orbit_radius no puede ser negativo (se obtuvo {})", name, body.orbit_radius));
+    }
+    if !body.orbit_speed.is_finite() {
+        return Err(format!("Cuerpo '{}': orbit_speed debe ser finito", name));
+    }
+    if let Some(speed) = body.rotation_speed {
+        if speed.iter().any(|v| !v.is_finite()) {
+            return Err(format!("Cuerpo '{}': rotation_speed debe ser finito", name));
+        }
+    }
+    if let Some(ring) = &body.ring {
+        if !(ring.inner_radius.is_finite() && ring.outer_radius.is_finite() && ring.tilt.is_finite()) {
+            return Err(format!("Cuerpo '{}': los parámetros del anillo deben ser finitos", name));
+        }
+        if ring.inner_radius <= 0.0 || ring.outer_radius <= ring.inner_radius {
+            return Err(format!(
+                "Cuerpo '{}': el anillo necesita 0 < inner_radius < outer_radius (se obtuvo {} y {})",
+                name, ring.inner_radius, ring.outer_radius
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_atmosphere(file: &AtmosphereFile) -> Result<Atmosphere, String> {
+    let preset = match file.preset.as_deref().unwrap_or("earth") {
+        "earth" => Atmosphere::earth(),
+        "neptune" => Atmosphere::neptune(),
+        other => return Err(format!("preset de atmósfera desconocido '{}' (disponibles: earth, neptune)", other)),
+    };
+
+    let atmosphere = Atmosphere {
+        radius: file.radius.unwrap_or(preset.radius),
+        rayleigh: file.rayleigh.map_or(preset.rayleigh, |[r, g, b]| HdrColor::new(r, g, b)),
+        rayleigh_scale_height: file.rayleigh_scale_height.unwrap_or(preset.rayleigh_scale_height),
+        mie: file.mie.unwrap_or(preset.mie),
+        mie_scale_height: file.mie_scale_height.unwrap_or(preset.mie_scale_height),
+        mie_g: file.mie_g.unwrap_or(preset.mie_g),
+        intensity: file.intensity.unwrap_or(preset.intensity),
+    };
+
+    if !atmosphere.radius.is_finite() || atmosphere.radius <= 1.0 {
+        return Err(format!("atmosphere.radius debe ser mayor que 1 (se obtuvo {})", atmosphere.radius));
+    }
+    let coefficients = [atmosphere.rayleigh.r, atmosphere.rayleigh.g, atmosphere.rayleigh.b, atmosphere.mie, atmosphere.intensity];
+    if coefficients.iter().any(|value| !value.is_finite() || *value < 0.0) {
+        return Err("atmosphere: rayleigh, mie e intensity no pueden ser negativos".to_string());
+    }
+    for (field, height) in [("rayleigh_scale_height", atmosphere.rayleigh_scale_height), ("mie_scale_height", atmosphere.mie_scale_height)] {
+        if !height.is_finite() || height <= 0.0 {
+            return Err(format!("atmosphere.{} debe ser mayor que 0 (se obtuvo {})", field, height));
+        }
+    }
+    if !atmosphere.mie_g.is_finite() || atmosphere.mie_g.abs() >= 1.0 {
+        return Err(format!("atmosphere.mie_g debe estar entre -1 y 1 (se obtuvo {})", atmosphere.mie_g));
+    }
+
+    Ok(atmosphere)
+}
+
+fn parse_glow(file: &GlowFile) -> Result<Glow, String> {
+    let sun = Glow::sun();
+    let glow = Glow {
+        radius: file.radius.unwrap_or(sun.radius),
+        color: file.color.map_or(sun.color, |[r, g, b]| HdrColor::from(Color::new(r, g, b))),
+        intensity: file.intensity.unwrap_or(sun.intensity),
+    };
+
+    if !glow.radius.is_finite() || glow.radius <= 1.0 {
+        return Err(format!("glow.radius debe ser mayor que 1 (se obtuvo {})", glow.radius));
+    }
+    if !glow.intensity.is_finite() || glow.intensity < 0.0 {
+        return Err(format!("glow.intensity no puede ser negativa (se obtuvo {})", glow.intensity));
+    }
+
+    Ok(glow)
+}
+
+fn validate_mesh(mesh: &MeshFile) -> Result<(), String> {
+    match *mesh {
+        MeshFile::UvSphere { segments, rings } => {
+            if !(3..=1024).contains(&segments) || !(2..=1024).contains(&rings) {
+                return Err(format!(
+                    "uv_sphere necesita 3 <= segments <= 1024 y 2 <= rings <= 1024 (se obtuvo {} y {})",
+                    segments, rings
+                ));
+            }
+        }
+        MeshFile::Icosphere { subdivisions } => {
+            if subdivisions > 6 {
+                return Err(format!("icosphere admite hasta 6 subdivisiones (se obtuvo {})", subdivisions));
+            }
+        }
+        MeshFile::Torus { major_radius, minor_radius, segments, sides } => {
+            if !(major_radius.is_finite() && minor_radius.is_finite())
+                || minor_radius <= 0.0
+                || major_radius <= minor_radius
+            {
+                return Err(format!(
+                    "torus necesita 0 < minor_radius < major_radius (se obtuvo {} y {})",
+                    minor_radius, major_radius
+                ));
+            }
+            if !(3..=1024).contains(&segments) || !(3..=1024).contains(&sides) {
+                return Err(format!(
+                    "torus necesita entre 3 y 1024 segments y sides (se obtuvo {} y {})",
+                    segments, sides
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Posición en el mundo de un cuerpo (en cualquier nivel) según el estado de sus órbitas
+pub fn world_position(bodies: &[Planet], name: &str) -> Option<Vec3> {
+    find_placed(bodies, name).map(|(_, position, _)| position)
+}
+
+// Un cuerpo con su posición y escala final en el mundo
+fn find_placed<'a>(bodies: &'a [Planet], name: &str) -> Option<(&'a Planet, Vec3, f32)> {
+    fn search<'a>(bodies: &'a [Planet], name: &str, origin: Vec3, parent_scale: f32) -> Option<(&'a Planet, Vec3, f32)> {
+        bodies.iter().find_map(|body| {
+            let position = origin + body.position * parent_scale;
+            let scale = body.scale * parent_scale;
+            if body.name == name {
+                Some((body, position, scale))
+            } else {
+                search(&body.moons, name, position, scale)
+            }
+        })
+    }
+
+    search(bodies, name, Vec3::zeros(), 1.0)
+}
+
+// Radio en espacio de modelo: el de su malla o el de la esfera compartida
+fn body_radius(body: &Planet) -> f32 {
+    body.lod.as_ref().map_or(SPHERE_RADIUS, |chain| chain.bounding_radius())
+}
+
+// Todos los cuerpos como esferas que hacen sombra, en el orden de la
+// jerarquía (los anillos no cuentan); pasados MAX_OCCLUDERS se ignoran
+pub fn occluders(bodies: &[Planet]) -> Occluders {
+    fn collect(bodies: &[Planet], origin: Vec3, parent_scale: f32, out: &mut Vec<Occluder>) {
+        for body in bodies {
+            let position = origin + body.position * parent_scale;
+            let scale = body.scale * parent_scale;
+            out.push(Occluder { center: position, radius: body_radius(body) * scale });
+            collect(&body.moons, position, scale, out);
+        }
+    }
+
+    let mut spheres = Vec::new();
+    collect(bodies, Vec3::zeros(), 1.0, &mut spheres);
+    spheres.into_iter().collect()
+}
+
+fn parse_light(light: &SceneLightFile, bodies: &[BodyFile]) -> Result<SceneLight, String> {
+    let (color, intensity) = match light {
+        SceneLightFile::Point { color, intensity, .. } | SceneLightFile::Directional { color, intensity, .. } => {
+            (color.map_or(HdrColor::new(1.0, 1.0, 1.0), |[r, g, b]| HdrColor::from(Color::new(r, g, b))), intensity.unwrap_or(1.0))
+        }
+    };
+    if !intensity.is_finite() || intensity < 0.0 {
+        return Err(format!("lights: intensity no puede ser negativa (se obtuvo {})", intensity));
+    }
+
+    match light {
+        SceneLightFile::Point { position, body, falloff, radius, .. } => {
+            if !falloff.is_finite() || *falloff < 0.0 {
+                return Err(format!("lights: falloff no puede ser negativo (se obtuvo {})", falloff));
+            }
+            if !radius.is_finite() || *radius < 0.0 {
+                return Err(format!("lights: radius no puede ser negativo (se obtuvo {})", radius));
+            }
+            let position = match (position, body) {
+                (Some([x, y, z]), None) => {
+                    let position = Vec3::new(*x, *y, *z);
+                    if !is_finite_vec(&position) {
+                        return Err("lights: position debe ser un vector finito".to_string());
+                    }
+                    position
+                }
+                (None, Some(name)) => {
+                    if !bodies.iter().any(|b| &b.name == name) {
+                        return Err(format!("lights: el cuerpo '{}' no existe", name));
+                    }
+                    Vec3::zeros()
+                }
+                _ => return Err("lights: una luz puntual necesita `position` o `body` (solo uno)".to_string()),
+            };
+            Ok(SceneLight { light: Light::point(position, color, intensity, *falloff).with_radius(*radius), body: body.clone() })
+        }
+        SceneLightFile::Directional { direction, .. } => {
+            let direction = Vec3::new(direction[0], direction[1], direction[2]);
+            if !is_finite_vec(&direction) || direction.magnitude() == 0.0 {
+                return Err("lights: direction debe ser un vector finito distinto de cero".to_string());
+            }
+            Ok(SceneLight { light: Light::directional(direction, color, intensity), body: None })
+        }
+    }
+}
+
+fn is_finite_vec(v: &Vec3) -> bool {
+    v.x.is_finite() && v.y.is_finite() && v.z.is_finite()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<Scene, String> {
+        let mut shaders = ShaderRegistry::with_builtin_shaders();
+        Scene::parse(contents, Path::new("."), &mut shaders)
+    }
+
+    fn error(contents: &str) -> String {
+        parse(contents).err().expect("la escena debería ser inválida")
+    }
+
+    #[test]
+    fn moons_and_rings_hang_from_their_parents() {
+        let scene = parse(r#"
+            [[bodies]]
+            name = "Saturno"
+            shader = "gas_saturn"
+            scale = 1.2
+            orbit_radius = 12.0
+            ring = { inner_radius = 1.3, outer_radius = 2.2, tilt = 0.4 }
+
+            [[bodies]]
+            name = "Titán"
+            shader = "moon"
+            scale = 0.2
+            parent = "Saturno"
+            orbit_radius = 2.5
+
+            [[bodies]]
+            name = "Marte"
+            shader = "rocky_mars"
+            scale = 0.5
+        "#)
+        .unwrap();
+
+        let names: Vec<&str> = scene.bodies.iter().map(|body| body.name.as_str()).collect();
+        assert_eq!(names, ["Saturno", "Marte"]);
+        assert_eq!(scene.bodies[0].moons.len(), 1);
+        assert_eq!(scene.bodies[0].moons[0].name, "Titán");
+        assert!(scene.bodies[0].ring.is_some());
+        assert!(scene.bodies[1].moons.is_empty());
+    }
+
+    #[test]
+    fn invalid_bodies_name_the_body_and_the_value() {
+        let body = |fields: &str| format!("[[bodies]]\nname = \"Marte\"\n{}\n", fields);
+
+        let unknown = error(&body("shader = \"plasma\"\nscale = 1.0"));
+        assert!(unknown.starts_with("Cuerpo 'Marte': Shader desconocido 'plasma' (disponibles: "), "{}", unknown);
+        assert!(unknown.contains("rocky_mars"), "{}", unknown);
+
+        let cases = [
+            ("shader = \"rocky_mars\"\nscale = 0.0", "Cuerpo 'Marte': scale debe ser mayor que 0 (se obtuvo 0)"),
+            ("shader = \"rocky_mars\"\nscale = nan", "Cuerpo 'Marte': scale debe ser mayor que 0 (se obtuvo NaN)"),
+            ("shader = \"rocky_mars\"\nscale = 1.0\norbit_radius = -2.0", "Cuerpo 'Marte': orbit_radius no puede ser negativo (se obtuvo -2)"),
+            ("shader = \"rocky_mars\"\nscale = 1.0\norbit_speed = inf", "Cuerpo 'Marte': orbit_speed debe ser finito"),
+            ("shader = \"rocky_mars\"\nscale = 1.0\nrotation_speed = [0.0, nan, 0.0]", "Cuerpo 'Marte': rotation_speed debe ser finito"),
+            (
+                "shader = \"rocky_mars\"\nscale = 1.0\nring = { inner_radius = 2.0, outer_radius = 1.5 }",
+                "Cuerpo 'Marte': el anillo necesita 0 < inner_radius < outer_radius (se obtuvo 2 y 1.5)",
+            ),
+            ("shader = \"material\"\nscale = 1.0", "Cuerpo 'Marte': shader = \"material\" requiere `model`"),
+            ("shader = \"textured\"\nscale = 1.0", "Cuerpo 'Marte': shader = \"textured\" requiere `texture`"),
+            ("shader = \"rocky_mars\"\nscale = 1.0\ntexture = \"marte.png\"", "Cuerpo 'Marte': `texture` solo aplica con shader = \"textured\""),
+            (
+                "shader = \"textured\"\nscale = 1.0\ntexture = \"marte.png\"\nwrap_v = \"tile\"",
+                "Cuerpo 'Marte': wrap_v desconocido 'tile' (usa repeat, mirror o clamp)",
+            ),
+        ];
+        for (fields, expected) in cases {
+            assert_eq!(error(&body(fields)), expected);
+        }
+
+        assert_eq!(
+            error("[[bodies]]\nname = \" \"\nshader = \"moon\"\nscale = 1.0\n"),
+            "Todos los cuerpos necesitan un nombre"
+        );
+        assert!(error(&body("shader = \"moon\"\nscale = 1.0\ncolor = 3")).starts_with("TOML inválido: "));
+    }
+
+    #[test]
+    fn generated_meshes_are_bounded() {
+        let body = |mesh: &str| format!("[[bodies]]\nname = \"Toro\"\nshader = \"moon\"\nscale = 1.0\nmesh = {}\n", mesh);
+
+        assert!(parse(&body("{ type = \"icosphere\", subdivisions = 2 }")).is_ok());
+        assert_eq!(
+            error(&body("{ type = \"icosphere\", subdivisions = 7 }")),
+            "Cuerpo 'Toro': icosphere admite hasta 6 subdivisiones (se obtuvo 7)"
+        );
+        assert_eq!(
+            error(&body("{ type = \"uv_sphere\", segments = 2 }")),
+            "Cuerpo 'Toro': uv_sphere necesita 3 <= segments <= 1024 y 2 <= rings <= 1024 (se obtuvo 2 y 24)"
+        );
+        assert_eq!(
+            error(&body("{ type = \"torus\", major_radius = 0.2, minor_radius = 0.3 }")),
+            "Cuerpo 'Toro': torus necesita 0 < minor_radius < major_radius (se obtuvo 0.3 y 0.2)"
+        );
+        assert_eq!(
+            error(&format!("{}model = \"sphere.obj\"\n", body("{ type = \"torus\" }"))),
+            "Cuerpo 'Toro': usa `model` o `mesh`, no ambos"
+        );
+        assert_eq!(
+            error(&format!("{}strict = false\n", body("{ type = \"torus\" }"))),
+            "Cuerpo 'Toro': `strict` solo aplica con `model`"
+        );
+        assert_eq!(
+            error(&format!("{}normals = \"flat\"\n", body("{ type = \"torus\" }"))),
+            "Cuerpo 'Toro': `normals` solo aplica con `model`"
+        );
+    }
+
+    #[test]
+    fn lenient_models_load_with_warnings() {
+        let dir = std::env::temp_dir().join(format!("scene_strict_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("roto.obj"), "v 0 0 0\nv 1 nan 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+
+        let body = |strict: &str| format!(
+            "[[bodies]]\nname = \"Roca\"\nshader = \"moon\"\nscale = 1.0\nmodel = \"roto.obj\"\n{}",
+            strict
+        );
+        let mut shaders = ShaderRegistry::with_builtin_shaders();
+        let strict = Scene::parse(&body(""), &dir, &mut shaders).err();
+        let lenient = Scene::parse(&body("strict = false\n"), &dir, &mut shaders);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(strict.unwrap().starts_with("Cuerpo 'Roca': "));
+        let scene = lenient.unwrap();
+        assert_eq!(scene.bodies.len(), 1);
+        assert_eq!(scene.warnings.len(), 1);
+        assert!(scene.warnings[0].starts_with("Cuerpo 'Roca': "), "{}", scene.warnings[0]);
+        assert!(scene.warnings[0].contains("roto.obj:2:"), "{}", scene.warnings[0]);
+    }
+
+    #[test]
+    fn model_normals_can_be_recomputed() {
+        let dir = std::env::temp_dir().join(format!("scene_normals_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Tetraedro con una sola normal hacia arriba para todas las esquinas
+        fs::write(dir.join("tetra.obj"), "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+vn 0 1 0
+f 1//1 3//1 2//1
+f 1//1 2//1 4//1
+f 1//1 4//1 3//1
+f 2//1 3//1 4//1
+").unwrap();
+
+        let normals = |option: &str| {
+            let contents = format!(
+                "[[bodies]]\nname = \"Tetra\"\nshader = \"moon\"\nscale = 1.0\nmodel = \"tetra.obj\"\n{}",
+                option
+            );
+            Scene::parse(&contents, &dir, &mut ShaderRegistry::with_builtin_shaders()).map(|scene| {
+                let model = scene.bodies[0].lod.as_ref().unwrap().finest();
+                model.vertices.iter().map(|vertex| vertex.normal).collect::<Vec<_>>()
+            })
+        };
+        let file = normals("");
+        let smooth = normals("normals = \"smooth\"\n");
+        let flat = normals("normals = \"flat\"\n");
+        let unknown = normals("normals = \"facetado\"\n").err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert!(file.unwrap().iter().all(|normal| *normal == up));
+        // Suaves: una normal por esquina, promedio de las caras vecinas
+        let smooth = smooth.unwrap();
+        assert_eq!(smooth.len(), 4);
+        assert!(smooth.iter().any(|normal| (normal - up).magnitude() > 0.1));
+        // Planas: cada cara tiene sus propias esquinas
+        assert_eq!(flat.unwrap().len(), 12);
+        assert_eq!(unknown.unwrap(), "Cuerpo 'Tetra': normals desconocido 'facetado' (usa file, smooth o flat)");
+    }
+
+    #[test]
+    fn atmospheres_start_from_a_preset() {
+        let body = |atmosphere: &str| format!("[[bodies]]\nname = \"Neptuno\"\nshader = \"ice_neptune\"\nscale = 1.0\natmosphere = {}\n", atmosphere);
+
+        let scene = parse(&body("{ preset = \"neptune\", radius = 1.3, mie_g = -0.2 }")).unwrap();
+        let expected = Atmosphere { radius: 1.3, mie_g: -0.2, ..Atmosphere::neptune() };
+        assert_eq!(scene.bodies[0].atmosphere, Some(expected));
+        assert_eq!(parse(&body("{}")).unwrap().bodies[0].atmosphere, Some(Atmosphere::earth()));
+
+        let cases = [
+            ("{ preset = \"venus\" }", "preset de atmósfera desconocido 'venus' (disponibles: earth, neptune)"),
+            ("{ radius = 0.9 }", "atmosphere.radius debe ser mayor que 1 (se obtuvo 0.9)"),
+            ("{ rayleigh = [1.0, -2.0, 3.0] }", "atmosphere: rayleigh, mie e intensity no pueden ser negativos"),
+            ("{ mie_scale_height = 0.0 }", "atmosphere.mie_scale_height debe ser mayor que 0 (se obtuvo 0)"),
+            ("{ mie_g = 1.0 }", "atmosphere.mie_g debe estar entre -1 y 1 (se obtuvo 1)"),
+        ];
+        for (atmosphere, expected) in cases {
+            assert_eq!(error(&body(atmosphere)), format!("Cuerpo 'Neptuno': {}", expected));
+        }
+    }
+
+    #[test]
+    fn glows_start_from_the_sun() {
+        let body = |glow: &str| format!("[[bodies]]\nname = \"Sol\"\nshader = \"sun\"\nscale = 1.0\nglow = {}\n", glow);
+
+        assert_eq!(parse(&body("{}")).unwrap().bodies[0].glow, Some(Glow::sun()));
+        let scene = parse(&body("{ radius = 2.0, intensity = 0.5 }")).unwrap();
+        assert_eq!(scene.bodies[0].glow, Some(Glow { radius: 2.0, intensity: 0.5, ..Glow::sun() }));
+
+        assert_eq!(error(&body("{ radius = 1.0 }")), "Cuerpo 'Sol': glow.radius debe ser mayor que 1 (se obtuvo 1)");
+        assert_eq!(error(&body("{ intensity = -1.0 }")), "Cuerpo 'Sol': glow.intensity no puede ser negativa (se obtuvo -1)");
+    }
+
+    #[test]
+    fn broken_hierarchies_are_rejected() {
+        let body = |name: &str, parent: Option<&str>| {
+            let parent = parent.map(|p| format!("parent = \"{}\"\n", p)).unwrap_or_default();
+            format!("[[bodies]]\nname = \"{}\"\nshader = \"moon\"\nscale = 1.0\n{}\n", name, parent)
+        };
+
+        let duplicated = format!("{}{}", body("Luna", None), body("Luna", None));
+        assert_eq!(error(&duplicated), "Cuerpo duplicado: 'Luna'");
+
+        assert_eq!(error(&body("Luna", Some("Tierra"))), "Cuerpo 'Luna': el padre 'Tierra' no existe");
+        assert_eq!(error(&body("Luna", Some("Luna"))), "Cuerpo 'Luna': no puede ser su propio padre");
+
+        let cycle = format!("{}{}{}", body("Sol", None), body("Fobos", Some("Deimos")), body("Deimos", Some("Fobos")));
+        assert_eq!(error(&cycle), "Cuerpo 'Fobos': ciclo en la jerarquía de padres");
+    }
+
+    #[test]
+    fn lights_are_validated() {
+        let sun = "[[bodies]]\nname = \"Sol\"\nshader = \"sun\"\nscale = 2.0\n";
+        let light = |fields: &str| format!("{}[[lights]]\n{}\n", sun, fields);
+
+        let scene = parse(&light("type = \"point\"\nbody = \"Sol\"\nintensity = 2.0")).unwrap();
+        assert_eq!(scene.lights.len(), 1);
+        assert_eq!(scene.lights[0].body.as_deref(), Some("Sol"));
+
+        let cases = [
+            ("type = \"point\"\nbody = \"Vega\"", "lights: el cuerpo 'Vega' no existe"),
+            ("type = \"point\"", "lights: una luz puntual necesita `position` o `body` (solo uno)"),
+            ("type = \"point\"\nbody = \"Sol\"\nposition = [0.0, 0.0, 0.0]", "lights: una luz puntual necesita `position` o `body` (solo uno)"),
+            ("type = \"point\"\nposition = [0.0, inf, 0.0]", "lights: position debe ser un vector finito"),
+            ("type = \"point\"\nbody = \"Sol\"\nintensity = -1.0", "lights: intensity no puede ser negativa (se obtuvo -1)"),
+            ("type = \"point\"\nbody = \"Sol\"\nfalloff = -0.5", "lights: falloff no puede ser negativo (se obtuvo -0.5)"),
+            ("type = \"directional\"\ndirection = [0.0, 0.0, 0.0]", "lights: direction debe ser un vector finito distinto de cero"),
+        ];
+        for (fields, expected) in cases {
+            assert_eq!(error(&light(fields)), expected);
+        }
+
+        let crowded = (0..=MAX_LIGHTS).map(|_| "[[lights]]\ntype = \"directional\"\ndirection = [1.0, 0.0, 0.0]\n").collect::<String>();
+        assert_eq!(
+            error(&crowded),
+            format!("La escena declara {} luces; se admiten como máximo {}", MAX_LIGHTS + 1, MAX_LIGHTS)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use crate::color::{HdrColor, Rgba};
+use crate::fragment::Fragment;
+use crate::vertex::Vertex;
+use crate::shaders::{
+    self, Uniforms, sun_shader, mars_shader, earth_shader, jupiter_shader, saturn_shader,
+    neptune_shader, moon_shader, ring_shader, test_shader, default_shader,
+};
+
+use std::collections::BTreeMap;
+
+pub const DEFAULT_VERTEX_SHADER: &str = "default";
+
+type FragmentFn = fn(&Fragment, &Uniforms) -> HdrColor;
+
+// Etapa de vértices: transforma un vértice de espacio de modelo a espacio de
+// clip (`clip_position`); el recorte y el paso a pantalla los hace el pipeline
+pub trait VertexShader: Send + Sync {
+    fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex;
+}
+
+// Etapa de fragmentos: calcula el color (HDR lineal) y la opacidad de un fragmento
+pub trait FragmentShader: Send + Sync {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba;
+}
+
+// Cualquier función con la firma correcta puede usarse como shader; los
+// shaders de fragmentos pueden devolver `Color` (sRGB), `HdrColor` (opacos) o `Rgba`
+impl<F> VertexShader for F
+where
+    F: Fn(&Vertex, &Uniforms) -> Vertex + Send + Sync,
+{
+    fn transform(&self, vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+        self(vertex, uniforms)
+    }
+}
+
+impl<F, C> FragmentShader for F
+where
+    F: Fn(&Fragment, &Uniforms) -> C + Send + Sync,
+    C: Into<Rgba>,
+{
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        self(fragment, uniforms).into()
+    }
+}
+
+// Asocia nombres (los que usan la escena y la línea de comandos) con shaders
+#[derive(Default)]
+pub struct ShaderRegistry {
+    vertex_shaders: BTreeMap<String, Box<dyn VertexShader>>,
+    fragment_shaders: BTreeMap<String, Box<dyn FragmentShader>>,
+}
+
+impl ShaderRegistry {
+    pub fn new() -> Self {
+        ShaderRegistry::default()
+    }
+
+    pub fn with_builtin_shaders() -> Self {
+        let mut registry = ShaderRegistry::new();
+
+        let builtin_fragment: [(&str, FragmentFn); 9] = [
+            ("sun", sun_shader),
+            ("rocky_mars", mars_shader),
+            ("rocky_earth", earth_shader),
+            ("gas_jupiter", jupiter_shader),
+            ("gas_saturn", saturn_shader),
+            ("ice_neptune", neptune_shader),
+            ("moon", moon_shader),
+            ("test", test_shader),
+            ("default", default_shader),
+        ];
+
+        registry
+            .register_vertex(DEFAULT_VERTEX_SHADER, shaders::vertex_shader)
+            .expect("shader de vértices integrado duplicado");
+        for (name, shader) in builtin_fragment {
+            registry
+                .register_fragment(name, shader)
+                .expect("shader integrado duplicado");
+        }
+        // El de los anillos devuelve opacidad además de color
+        registry
+            .register_fragment("ring", ring_shader)
+            .expect("shader integrado duplicado");
+
+        registry
+    }
+
+    pub fn register_vertex<S: VertexShader + 'static>(&mut self, name: &str, shader: S) -> Result<(), String> {
+        if self.vertex_shaders.contains_key(name) {
+            return Err(format!("Ya existe un shader de vértices llamado '{}'", name));
+        }
+        self.vertex_shaders.insert(name.to_string(), Box::new(shader));
+        Ok(())
+    }
+
+    pub fn register_fragment<S: FragmentShader + 'static>(&mut self, name: &str, shader: S) -> Result<(), String> {
+        if self.fragment_shaders.contains_key(name) {
+            return Err(format!("Ya existe un shader de fragmentos llamado '{}'", name));
+        }
+        self.fragment_shaders.insert(name.to_string(), Box::new(shader));
+        Ok(())
+    }
+
+    pub fn vertex(&self, name: &str) -> Result<&dyn VertexShader, String> {
+        self.vertex_shaders
+            .get(name)
+            .map(|shader| shader.as_ref())
+            .ok_or_else(|| format!(
+                "Shader de vértices desconocido '{}' (disponibles: {})",
+                name,
+                self.vertex_names().join(", ")
+            ))
+    }
+
+    pub fn fragment(&self, name: &str) -> Result<&dyn FragmentShader, String> {
+        self.fragment_shaders
+            .get(name)
+            .map(|shader| shader.as_ref())
+            .ok_or_else(|| format!(
+                "Shader desconocido '{}' (disponibles: {})",
+                name,
+                self.fragment_names().join(", ")
+            ))
+    }
+
+    pub fn vertex_names(&self) -> Vec<&str> {
+        self.vertex_shaders.keys().map(|name| name.as_str()).collect()
+    }
+
+    pub fn fragment_names(&self) -> Vec<&str> {
+        self.fragment_shaders.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use nalgebra_glm::{Mat4, Vec2, Vec3};
+
+    fn uniforms() -> Uniforms {
+        Uniforms {
+            model_matrix: Mat4::new_translation(&Vec3::new(0.0, 2.0, 0.0)),
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: Mat4::identity(),
+            time: 0.5,
+            lights: crate::light::Lights::default(),
+            camera_position: Vec3::zeros(),
+            occluders: crate::occlusion::Occluders::default(),
+            body_radius: 1.0,
+        }
+    }
+
+    #[test]
+    fn unknown_names_list_the_available_shaders() {
+        let registry = ShaderRegistry::with_builtin_shaders();
+
+        assert_eq!(
+            registry.vertex("ondas").err().unwrap(),
+            "Shader de vértices desconocido 'ondas' (disponibles: default)"
+        );
+        assert_eq!(
+            registry.fragment("plasma").err().unwrap(),
+            "Shader desconocido 'plasma' (disponibles: default, gas_jupiter, gas_saturn, ice_neptune, \
+             moon, ring, rocky_earth, rocky_mars, sun, test)"
+        );
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let mut registry = ShaderRegistry::with_builtin_shaders();
+
+        assert_eq!(
+            registry.register_fragment("sun", default_shader).err().unwrap(),
+            "Ya existe un shader de fragmentos llamado 'sun'"
+        );
+        assert_eq!(
+            registry.register_vertex(DEFAULT_VERTEX_SHADER, shaders::vertex_shader).err().unwrap(),
+            "Ya existe un shader de vértices llamado 'default'"
+        );
+        // Rechazarlo no agrega ni reemplaza nada
+        assert_eq!(registry.fragment_names().len(), 10);
+    }
+
+    #[test]
+    fn closures_can_be_registered_and_used() {
+        let mut registry = ShaderRegistry::new();
+        let tint = HdrColor::new(0.5, 1.0, 2.0);
+        registry
+            .register_fragment("tinte", move |fragment: &Fragment, uniforms: &Uniforms| {
+                Rgba::new(tint * fragment.tex_coords.x, uniforms.time)
+            })
+            .unwrap();
+        registry
+            .register_vertex("elevar", |vertex: &Vertex, uniforms: &Uniforms| {
+                let mut moved = vertex.clone();
+                moved.world_position = (uniforms.model_matrix * vertex.position.push(1.0)).xyz();
+                moved
+            })
+            .unwrap();
+
+        let uniforms = uniforms();
+        let mut fragment = Fragment::new(Color::black(), Vec3::new(0.0, 0.0, 1.0));
+        fragment.tex_coords = Vec2::new(2.0, 0.0);
+        let shaded = registry.fragment("tinte").unwrap().shade(&fragment, &uniforms);
+        assert_eq!(shaded, Rgba::new(HdrColor::new(1.0, 2.0, 4.0), 0.5));
+
+        let vertex = Vertex::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec2::zeros());
+        let moved = registry.vertex("elevar").unwrap().transform(&vertex, &uniforms);
+        assert_eq!(moved.world_position, Vec3::new(1.0, 2.0, 0.0));
+
+        assert_eq!(registry.fragment_names(), ["tinte"]);
+        assert_eq!(registry.vertex_names(), ["elevar"]);
+    }
+}
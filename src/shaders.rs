@@ -1,7 +1,9 @@
 use nalgebra_glm::{Vec3, Vec4, Mat4, Vec2};
 use crate::vertex::Vertex;
 use crate::fragment::Fragment;
-use crate::color::Color;
+use crate::color::{srgb_to_linear, Color, HdrColor, Rgba};
+use crate::light::Lights;
+use crate::occlusion::Occluders;
 
 pub struct Uniforms {
     pub model_matrix: Mat4,
@@ -9,7 +11,14 @@ pub struct Uniforms {
     pub projection_matrix: Mat4,
     pub viewport_matrix: Mat4,
     pub time: f32,
-    pub light_dir: Vec3,
+    pub lights: Lights,
+    // Posición de la cámara en el mundo, para fresnel y especulares
+    pub camera_position: Vec3,
+    // Cuerpos que hacen sombra analítica (vacío con otro método de sombras)
+    pub occluders: Occluders,
+    // Radio en el mundo del cuerpo que se dibuja; un anillo lleva el de su
+    // planeta, que puede tener una malla propia más grande que la esfera
+    pub body_radius: f32,
 }
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
@@ -20,17 +29,9 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         1.0
     );
 
-    let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
-
-    let w = transformed.w;
-    let ndc_position = Vec4::new(
-        transformed.x / w,
-        transformed.y / w,
-        transformed.z / w,
-        1.0
-    );
-
-    let screen_position = uniforms.viewport_matrix * ndc_position;
+    // Queda en espacio de clip: el pipeline recorta y divide por w después
+    let world_position = uniforms.model_matrix * position;
+    let clip_position = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
     let model_mat3 = Mat4::new(
         uniforms.model_matrix[(0, 0)], uniforms.model_matrix[(0, 1)], uniforms.model_matrix[(0, 2)], 0.0,
@@ -43,9 +44,19 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let transformed_normal = model_mat3 * normal4;
     let final_normal = Vec3::new(transformed_normal.x, transformed_normal.y, transformed_normal.z).normalize();
 
+    let tangent4 = Vec4::new(vertex.tangent.x, vertex.tangent.y, vertex.tangent.z, 0.0);
+    let transformed_tangent = model_mat3 * tangent4;
+
     let mut new_vertex = vertex.clone();
-    new_vertex.transformed_position = Vec3::new(screen_position.x, screen_position.y, screen_position.z);
+    new_vertex.clip_position = clip_position;
+    new_vertex.world_position = world_position.xyz();
     new_vertex.transformed_normal = final_normal;
+    new_vertex.transformed_tangent = Vec4::new(
+        transformed_tangent.x,
+        transformed_tangent.y,
+        transformed_tangent.z,
+        vertex.tangent.w,
+    );
 
     new_vertex
 }
@@ -53,55 +64,42 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 // ============================================
 // SISTEMA DE FRAGMENT SHADERS PARA PLANETAS
 // ============================================
+// Cada shader se registra por nombre en `shader_registry.rs`. Las capas se
+// mezclan con `Color` (sRGB de 8 bits) y el resultado sale como HdrColor
+// lineal; el sol y el reflejo de la Tierra usan valores por encima de 1.0
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: &str) -> Color {
-    match shader_type {
-        "sun" => sun_shader(fragment, uniforms),
-        "rocky_mars" => mars_shader(fragment, uniforms),
-        "rocky_earth" => earth_shader(fragment, uniforms),
-        "gas_jupiter" => jupiter_shader(fragment, uniforms),
-        "gas_saturn" => saturn_shader(fragment, uniforms),
-        "ice_neptune" => neptune_shader(fragment, uniforms),
-        "moon" => moon_shader(fragment, uniforms),
-        "ring" => ring_shader(fragment, uniforms),
-        "test" => test_shader(fragment, uniforms),
-        _ => default_shader(fragment, uniforms)
-    }
-}
+// Intensidad de emisión del sol (radiancia lineal)
+const SUN_EMISSION: f32 = 1.6;
+
+// Brillo del reflejo especular del sol sobre los océanos
+const EARTH_SPECULAR_STRENGTH: f32 = 2.5;
 
 // ============================================
 // SHADER DE PRUEBA (Con iluminación mejorada)
 // ============================================
-fn test_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let light_dir = uniforms.light_dir.normalize();
+pub fn test_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
     
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.2;
-    let intensity = ambient + diffuse * 0.8;
+    let diffuse = uniforms.lights.diffuse(fragment.world_position, normal);
     
     let base_color = Color::new(255, 255, 0);
-    base_color * intensity
+    shade_surface(base_color, 0.2, 0.8, diffuse)
 }
 
 // ============================================
 // SHADER DEFAULT (Con iluminación)
 // ============================================
-fn default_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let light_dir = uniforms.light_dir.normalize();
+pub fn default_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
     
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.2;
-    let intensity = ambient + diffuse * 0.8;
-    
-    fragment.color * intensity
+    let diffuse = uniforms.lights.diffuse(fragment.world_position, normal);
+    shade_surface(fragment.color, 0.2, 0.8, diffuse)
 }
 
 // ============================================
 // SHADER: SOL (Estrella) - 5 CAPAS
 // ============================================
-fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
@@ -183,7 +181,7 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // CAPA 5: Corona Solar
     let corona_color = Color::new(255, 200, 100);
     
-    let view_dir = Vec3::new(0.0, 0.0, 1.0);
+    let view_dir = view_direction(fragment, uniforms);
     let fresnel = 1.0 - nalgebra_glm::dot(&normal, &view_dir).abs();
     let fresnel_pow = fresnel.powf(2.0);
     
@@ -202,22 +200,21 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_flares
     };
     
-    // Brillo propio
-    let brightness_boost = 1.15;
-    
-    Color::new(
-        (color_with_corona.r as f32 * brightness_boost).min(255.0) as u8,
-        (color_with_corona.g as f32 * brightness_boost).min(255.0) as u8,
-        (color_with_corona.b as f32 * brightness_boost).min(255.0) as u8,
-    )
+    // Brillo propio: es una fuente de luz, así que emite por encima de 1.0 y
+    // las erupciones y la corona brillan todavía más; el tone mapping las
+    // comprime en vez de recortarlas a blanco
+    let flare_glow = smoothstep(flare_threshold, flare_threshold + 0.15, flare_noise);
+    let corona_glow = smoothstep(0.3, 1.0, fresnel_pow);
+    let emission = SUN_EMISSION * (1.0 + flare_glow * 0.8 + corona_glow * 0.6);
+
+    HdrColor::from(color_with_corona) * emission
 }
 
 // ============================================
 // SHADER: MARTE (Planeta Rocoso) - 4 CAPAS
 // ============================================
-fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
     // CAPA 1: Color Base Rojo-Naranja
@@ -295,17 +292,13 @@ fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.3;
-    let intensity = ambient + diffuse * 0.7;
-    
-    color_with_terrain * intensity
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    shade_surface(color_with_terrain, 0.3, 0.7, diffuse)
 }
 
-fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
-    let view_dir = Vec3::new(0.0, 0.0, 1.0);
+    let view_dir = view_direction(fragment, uniforms);
     let uv = get_uv_from_position(&fragment.normal);
 
     // ============================================================
@@ -395,35 +388,36 @@ fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let atmosphere_intensity = fresnel * 0.5;
     let color_with_atmosphere = mix_color(&color_with_clouds, &atmosphere_color, atmosphere_intensity);
 
+    // ============================================================
+    // ILUMINACIÓN FINAL
+    // ============================================================
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    let lit = shade_surface(color_with_atmosphere, 0.22, 0.78, diffuse);
+
     // ============================================================
     // EFECTO ESPECULAR sobre el océano
     // ============================================================
-    let half_dir = (light_dir + view_dir).normalize();
-    let spec = nalgebra_glm::dot(&normal, &half_dir).max(0.0).powf(80.0);
-    let specular_strength = 0.5;
-    let specular_color = Color::new(180, 220, 255) * (spec * specular_strength);
+    // Se suma en HDR después de iluminar: el reflejo del sol puede superar
+    // el blanco sin aplanar el océano alrededor
+    let specular_color = uniforms.lights.iter().fold(HdrColor::black(), |sum, light| {
+        let half_dir = (light.direction_from(fragment.world_position) + view_dir).normalize();
+        let spec = nalgebra_glm::dot(&normal, &half_dir).max(0.0).powf(80.0);
+        let visible = uniforms.occluders.visibility(fragment.world_position, light);
+        sum + HdrColor::from(Color::new(180, 220, 255)) * light.radiance_at(fragment.world_position) * (spec * EARTH_SPECULAR_STRENGTH * visible)
+    });
 
     // Aplicamos solo en regiones oceánicas (usando inverso del threshold)
     let ocean_factor = smoothstep(0.6, land_threshold - 0.05, continent_noise);
-    let color_with_specular = color_with_atmosphere + (specular_color * ocean_factor);
-
-    // ============================================================
-    // ILUMINACIÓN FINAL
-    // ============================================================
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.22;
-    let intensity = ambient + diffuse * 0.78;
 
-    color_with_specular * intensity
+    lit + specular_color * ocean_factor
 }
 
 
 // ============================================
 // SHADER: JÚPITER (Gigante Gaseoso) - 4 CAPAS
 // ============================================
-fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
     // CAPA 1: Bandas Horizontales
@@ -505,19 +499,15 @@ fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.30;
-    let intensity = ambient + diffuse * 0.70;
-    
-    pole_darkening * intensity
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    shade_surface(pole_darkening, 0.30, 0.70, diffuse)
 }
 
 // ============================================
 // SHADER: SATURNO (Gigante Gaseoso) - 4 CAPAS
 // ============================================
-fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
     // CAPA 1: Color Base Crema/Dorado
@@ -597,20 +587,22 @@ fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         color_with_turbulence
     };
     
-    // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.35;
-    let intensity = ambient + diffuse * 0.65;
+    // Oscurecimiento del limbo: hacia el borde visto desde la cámara se
+    // atraviesa más atmósfera
+    let facing = nalgebra_glm::dot(&normal, &view_direction(fragment, uniforms)).max(0.0);
+    let limb_color = Color::new(150, 125, 95);
+    let color_with_limb = mix_color(&color_with_hexagon, &limb_color, (1.0 - facing).powf(2.0) * 0.35);
     
-    color_with_hexagon * intensity
+    // Iluminación
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    shade_surface(color_with_limb, 0.35, 0.65, diffuse)
 }
 
 // ============================================
-// SHADER: NEPTUNO (Gigante de Hielo) - 4 CAPAS
+// SHADER: NEPTUNO (Gigante de Hielo) - 5 CAPAS
 // ============================================
-fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
     // CAPA 1: Color Base Azul Intenso
@@ -636,20 +628,21 @@ fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     
     let color_with_latitude = mix_color(&color_with_storms, &lighter_blue, latitude_factor);
     
-    // CAPA 4: Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.3;
-    let intensity = ambient + diffuse * 0.7;
+    // CAPA 4: Bruma de Metano en el borde visto desde la cámara
+    let fresnel = 1.0 - nalgebra_glm::dot(&normal, &view_direction(fragment, uniforms)).abs();
+    let haze_color = Color::new(150, 190, 255);
+    let color_with_haze = mix_color(&color_with_latitude, &haze_color, fresnel.powf(3.0) * 0.5);
     
-    color_with_latitude * intensity
+    // CAPA 5: Iluminación
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    shade_surface(color_with_haze, 0.3, 0.7, diffuse)
 }
 
 // ============================================
 // SHADER: LUNA (Satélite de la Tierra) - 3 CAPAS
 // ============================================
-fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> HdrColor {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     let uv = get_uv_from_position(&fragment.normal);
     
     // CAPA 1: Color Base Gris
@@ -680,14 +673,14 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         let local_x = (uv.x * scale) - grid_x;
         let local_y = (uv.y * scale) - grid_y;
         
-        let cell_hash = hash_2d(grid_x as i32 * 127 + layer as i32 * 311, 
-                                 grid_y as i32 * 257 + layer as i32 * 419);
+        let cell_hash = hash_2d(grid_x as i32 * 127 + layer * 311, 
+                                 grid_y as i32 * 257 + layer * 419);
         
         let crater_probability = 0.35 - layer as f32 * 0.1;
         
         if cell_hash > (1.0 - crater_probability) {
-            let offset_hash_x = hash_2d(grid_x as i32 * 73, grid_y as i32 * 151 + layer as i32);
-            let offset_hash_y = hash_2d(grid_x as i32 * 179, grid_y as i32 * 283 + layer as i32);
+            let offset_hash_x = hash_2d(grid_x as i32 * 73, grid_y as i32 * 151 + layer);
+            let offset_hash_y = hash_2d(grid_x as i32 * 179, grid_y as i32 * 283 + layer);
             
             let crater_center_x = 0.3 + offset_hash_x * 0.4;
             let crater_center_y = 0.3 + offset_hash_y * 0.4;
@@ -719,24 +712,18 @@ fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).max(0.0);
-    let ambient = 0.15;
-    let intensity = ambient + diffuse * 0.85;
-    
-    color_with_maria * intensity
+    let diffuse = surface_diffuse(fragment, uniforms, normal);
+    shade_surface(color_with_maria, 0.15, 0.85, diffuse)
 }
 
 // ============================================
 // SHADER: ANILLOS DE SATURNO - 3 CAPAS
 // ============================================
-fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
     let normal = fragment.normal.normalize();
-    let light_dir = uniforms.light_dir.normalize();
     
-    // Calcular distancia desde el centro
-    let pos = fragment.position;
-    let distance_from_center = (pos.x * pos.x + pos.y * pos.y).sqrt();
-    let normalized_dist = (distance_from_center - 400.0) / 400.0;
+    // Posición radial en el anillo: 0 en el borde interior, 1 en el exterior
+    let radial = fragment.tex_coords.x;
     
     // CAPA 1: Bandas Concéntricas
     let band1 = Color::new(220, 200, 170);
@@ -744,9 +731,9 @@ fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let band3 = Color::new(180, 160, 130);
     let gap_color = Color::new(50, 50, 50);
     
-    let band_pattern = (normalized_dist * 15.0).sin();
+    let band_pattern = (radial * 15.0).sin();
     
-    let base_color = if normalized_dist > 0.65 && normalized_dist < 0.70 {
+    let base_color = if in_cassini_division(radial) {
         gap_color
     } else if band_pattern > 0.6 {
         band1
@@ -757,29 +744,122 @@ fn ring_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     };
     
     // CAPA 2: Variación de Densidad
-    let density_noise = simple_noise(normalized_dist * 50.0, 0.0);
-    let color_with_density = mix_color(&base_color, &gap_color, density_noise * 0.3);
+    let color_with_density = mix_color(&base_color, &gap_color, ring_density(radial) * 0.3);
     
-    // CAPA 3: Transparencia Variable
-    let alpha = if normalized_dist > 0.65 && normalized_dist < 0.70 {
-        0.2
-    } else {
-        0.7 + density_noise * 0.2
-    };
+    // CAPA 3: Transparencia Variable (el renderer mezcla con lo que hay detrás)
+    let alpha = ring_opacity(radial);
     
-    let background = Color::new(10, 5, 20);
-    let final_color = mix_color(&background, &color_with_density, alpha);
+    // CAPA 4: Sombra del planeta. El anillo comparte centro con su planeta
+    // (sale de su propia matriz de modelo) y trae el radio en los uniforms
+    let center = (uniforms.model_matrix * Vec4::new(0.0, 0.0, 0.0, 1.0)).xyz();
+    let radius = uniforms.body_radius;
     
-    // Iluminación
-    let diffuse = nalgebra_glm::dot(&normal, &light_dir).abs().max(0.3);
+    // Iluminación (el anillo se ve desde ambos lados)
+    let point = fragment.world_position;
+    let diffuse = uniforms.lights.iter().fold(HdrColor::black(), |sum, light| {
+        let to_light = light.direction_from(point);
+        let lit = sphere_light(point, to_light, center, radius);
+        sum + light.radiance_at(point) * (nalgebra_glm::dot(&normal, &to_light).abs() * lit)
+    });
     
-    Color::new(
-        (final_color.r as f32 * diffuse) as u8,
-        (final_color.g as f32 * diffuse) as u8,
-        (final_color.b as f32 * diffuse) as u8,
+    Rgba::new(shade_surface(color_with_density, 0.0, 1.0, diffuse.map(|value| value.max(0.3))), alpha)
+}
+
+// Dirección unitaria del fragmento hacia la cámara, en espacio de mundo
+pub fn view_direction(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    (uniforms.camera_position - fragment.world_position).normalize()
+}
+
+// Difusa de las luces sobre un planeta o luna, con la sombra analítica de
+// los demás cuerpos (umbra y penumbra) sobre cada luz
+pub fn surface_diffuse(fragment: &Fragment, uniforms: &Uniforms, normal: Vec3) -> HdrColor {
+    let point = fragment.world_position;
+    uniforms.lights.diffuse_with(point, normal, |light| uniforms.occluders.visibility(point, light))
+}
+
+// Ilumina un color de superficie: `ambient + weight * difusa` escala el color
+// en sRGB, como antes de que hubiera luces de color, pero canal por canal con la
+// difusa que llega de las luces. Con una luz blanca de intensidad 1 el resultado
+// es el de siempre; luces más intensas pasan de 1.0 en HDR
+pub fn shade_surface(surface: Color, ambient: f32, weight: f32, diffuse: HdrColor) -> HdrColor {
+    let channel = |value: u8, light: f32| srgb_to_linear(value as f32 / 255.0 * (ambient + weight * light));
+    HdrColor::new(
+        channel(surface.r, diffuse.r),
+        channel(surface.g, diffuse.g),
+        channel(surface.b, diffuse.b),
     )
 }
 
+// ============================================
+// PERFIL Y SOMBRAS DE LOS ANILLOS
+// ============================================
+
+// Luz que conserva la superficie de un planeta a la sombra de su anillo
+const RING_SHADOW_AMBIENT: f32 = 0.2;
+
+fn in_cassini_division(radial: f32) -> bool {
+    radial > 0.65 && radial < 0.70
+}
+
+fn ring_density(radial: f32) -> f32 {
+    simple_noise(radial * 50.0, 0.0)
+}
+
+// Opacidad del anillo a una distancia radial (0 = borde interior, 1 = exterior);
+// fuera del anillo es transparente
+pub fn ring_opacity(radial: f32) -> f32 {
+    if !(0.0..=1.0).contains(&radial) {
+        0.0
+    } else if in_cassini_division(radial) {
+        0.2
+    } else {
+        0.7 + ring_density(radial) * 0.2
+    }
+}
+
+// Fracción de luz direccional que llega a `point` con una esfera en medio:
+// 1 iluminado, 0 en sombra, con un borde suave de ±5% del radio
+pub fn sphere_light(point: Vec3, light_dir: Vec3, center: Vec3, radius: f32) -> f32 {
+    let to_center = center - point;
+    let along = nalgebra_glm::dot(&to_center, &light_dir);
+    if along <= 0.0 {
+        return 1.0;
+    }
+
+    let closest = (to_center - light_dir * along).magnitude();
+    smoothstep(radius * 0.95, radius * 1.05, closest)
+}
+
+// Plano del anillo en espacio de mundo, para la sombra que proyecta sobre su planeta
+#[derive(Clone, Copy, Debug)]
+pub struct RingShadow {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+}
+
+impl RingShadow {
+    // Factor de luz en `point`: se sigue el rayo hacia la luz hasta el plano del
+    // anillo y se atenúa según su opacidad en ese radio
+    pub fn light_at(&self, point: Vec3, light_dir: Vec3) -> f32 {
+        let facing = nalgebra_glm::dot(&light_dir, &self.normal);
+        if facing.abs() < 1e-6 {
+            return 1.0;
+        }
+
+        let distance = nalgebra_glm::dot(&(self.center - point), &self.normal) / facing;
+        if distance <= 0.0 {
+            return 1.0;
+        }
+
+        let hit = point + light_dir * distance;
+        let radial = ((hit - self.center).magnitude() - self.inner_radius) / (self.outer_radius - self.inner_radius);
+        let transmitted = 1.0 - ring_opacity(radial);
+        RING_SHADOW_AMBIENT + (1.0 - RING_SHADOW_AMBIENT) * transmitted
+    }
+}
+
 // ============================================
 // FUNCIONES AUXILIARES
 // ============================================
@@ -895,4 +975,105 @@ pub fn get_uv_from_position(position: &Vec3) -> Vec2 {
     let u = 0.5 + (normalized.z.atan2(normalized.x)) / (2.0 * std::f32::consts::PI);
     let v = 0.5 - (normalized.y.asin()) / std::f32::consts::PI;
     Vec2::new(u, v)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::light::Light;
+    use crate::occlusion::Occluder;
+    use crate::primitives::SPHERE_RADIUS;
+    use crate::shader_registry::ShaderRegistry;
+
+    // Sol puntual en x = 10 y la cámara de la vista de planeta
+    fn uniforms() -> Uniforms {
+        let sun = Light::point(Vec3::new(10.0, 0.0, 0.0), HdrColor::new(1.0, 1.0, 1.0), 1.0, 0.0);
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: Mat4::identity(),
+            projection_matrix: Mat4::identity(),
+            viewport_matrix: Mat4::identity(),
+            time: 0.0,
+            lights: Lights::single(sun),
+            camera_position: Vec3::new(0.0, 0.0, 4.5),
+            occluders: Occluders::default(),
+            body_radius: SPHERE_RADIUS,
+        }
+    }
+
+    fn fragment_at(world_position: Vec3, normal: Vec3) -> Fragment {
+        let mut fragment = Fragment::new(Color::black(), normal);
+        fragment.world_position = world_position;
+        fragment
+    }
+
+    #[test]
+    fn rings_and_planets_shadow_each_other() {
+        let shadow = RingShadow { center: Vec3::zeros(), normal: Vec3::new(0.0, 1.0, 0.0), inner_radius: 2.4, outer_radius: 3.6 };
+        let up = Vec3::new(0.0, 1.0, 0.0);
+
+        // Bajo el anillo con la luz de arriba hay sombra; dentro del hueco
+        // central o por encima del plano no
+        assert!(shadow.light_at(Vec3::new(3.0, -0.5, 0.0), up) < 0.5);
+        assert_eq!(shadow.light_at(Vec3::new(1.0, -0.5, 0.0), up), 1.0);
+        assert_eq!(shadow.light_at(Vec3::new(3.0, 0.5, 0.0), up), 1.0);
+
+        // La esfera tapa la luz que viene del otro lado
+        let center = Vec3::zeros();
+        assert_eq!(sphere_light(Vec3::new(-3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), center, 1.0), 0.0);
+        assert_eq!(sphere_light(Vec3::new(-3.0, 2.0, 0.0), Vec3::new(1.0, 0.0, 0.0), center, 1.0), 1.0);
+        assert_eq!(sphere_light(Vec3::new(3.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), center, 1.0), 1.0);
+
+        // Detrás del planeta, a 1.5 de la línea hacia el sol: la esfera
+        // compartida no lo tapa, un cuerpo de radio 2 sí
+        let mut fragment = fragment_at(Vec3::new(-3.0, 0.0, 1.5), Vec3::new(1.0, 0.0, 0.0));
+        fragment.tex_coords = Vec2::new(0.3, 0.0);
+        let ring_light = |body_radius: f32| {
+            ring_shader(&fragment, &Uniforms { body_radius, ..uniforms() }).color.luminance()
+        };
+        assert!(ring_light(2.0) < ring_light(SPHERE_RADIUS) * 0.6);
+    }
+
+    #[test]
+    fn planet_shaders_fall_into_analytic_shadows() {
+        let registry = ShaderRegistry::with_builtin_shaders();
+        let sun = Light::point(Vec3::new(10.0, 0.0, 0.0), HdrColor::new(1.0, 1.0, 1.0), 1.0, 0.0).with_radius(0.5);
+
+        // El punto del planeta que mira al sol, con una luna justo en medio
+        let fragment = fragment_at(Vec3::new(SPHERE_RADIUS, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let planet = Occluder { center: Vec3::zeros(), radius: SPHERE_RADIUS };
+        let moon = Occluder { center: Vec3::new(2.0, 0.0, 0.0), radius: 0.3 };
+
+        let shade = |shader: &str, occluders: Occluders| {
+            let uniforms = Uniforms { lights: Lights::single(sun), occluders, ..uniforms() };
+            registry.fragment(shader).unwrap().shade(&fragment, &uniforms).color.luminance()
+        };
+
+        for shader in ["rocky_mars", "rocky_earth", "gas_jupiter", "gas_saturn", "ice_neptune", "moon"] {
+            let lit = shade(shader, [planet].into_iter().collect());
+            let eclipsed = shade(shader, [planet, moon].into_iter().collect());
+            assert!(eclipsed < lit * 0.7, "{} no cae en la sombra de la luna", shader);
+        }
+    }
+
+    #[test]
+    fn rims_follow_the_camera_position() {
+        let registry = ShaderRegistry::with_builtin_shaders();
+
+        // Un punto del ecuador mirando a +x: de frente para una cámara en +x,
+        // en el borde para una cámara en +z
+        let fragment = fragment_at(Vec3::new(SPHERE_RADIUS, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let shade = |shader: &str, camera_position: Vec3| {
+            let uniforms = Uniforms { camera_position, ..uniforms() };
+            registry.fragment(shader).unwrap().shade(&fragment, &uniforms).color
+        };
+
+        for shader in ["sun", "rocky_earth", "gas_saturn", "ice_neptune"] {
+            let facing = shade(shader, Vec3::new(5.0, 0.0, 0.0));
+            let grazing = shade(shader, Vec3::new(0.0, 0.0, 5.0));
+            assert!(facing != grazing, "{} no depende de la cámara", shader);
+        }
+
+        // La corona del sol brilla en el borde
+        assert!(shade("sun", Vec3::new(0.0, 0.0, 5.0)).luminance() > shade("sun", Vec3::new(5.0, 0.0, 0.0)).luminance());
+    }
+}
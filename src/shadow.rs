@@ -0,0 +1,194 @@
+use crate::clipping::clip_triangle;
+use crate::obj_loader::Model;
+use crate::shaders::create_viewport_matrix;
+use crate::vertex::Vertex;
+
+use nalgebra_glm::{look_at, ortho, Mat4, Vec3, Vec4};
+
+// ============================================
+// SHADOW MAPPING (profundidad vista desde la luz)
+// ============================================
+
+// Cómo se sombrean los cuerpos entre sí (eclipses y tránsitos)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ShadowMethod {
+    // Sin sombras entre cuerpos (las de los anillos se mantienen)
+    None,
+    // Esferas analíticas en `Uniforms`: umbra y penumbra exactas para cuerpos
+    // esféricos, casi sin costo
+    #[default]
+    Analytic,
+    // Mapa de sombras por sistema (planeta y lunas): sirve para cualquier malla
+    Map,
+}
+
+impl ShadowMethod {
+    pub const ALL: [ShadowMethod; 3] = [ShadowMethod::None, ShadowMethod::Analytic, ShadowMethod::Map];
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(ShadowMethod::None),
+            "analytic" => Ok(ShadowMethod::Analytic),
+            "map" => Ok(ShadowMethod::Map),
+            _ => Err(format!("Método de sombras desconocido '{}' (disponibles: none, analytic, map)", value)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShadowMethod::None => "none",
+            ShadowMethod::Analytic => "analytic",
+            ShadowMethod::Map => "map",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = ShadowMethod::ALL.iter().position(|method| method == self).unwrap_or(0);
+        ShadowMethod::ALL[(index + 1) % ShadowMethod::ALL.len()]
+    }
+}
+
+// Resolución por defecto del mapa (texels por lado)
+pub const SHADOW_MAP_SIZE: usize = 1024;
+
+// Sesgo de profundidad y desplazamiento sobre la normal, en texels: evitan que
+// una superficie se sombree a sí misma por el redondeo del mapa
+const DEPTH_BIAS_TEXELS: f32 = 1.5;
+const NORMAL_OFFSET_TEXELS: f32 = 1.5;
+
+// Profundidades de lo más cercano a la luz, con proyección ortográfica: el
+// sol está lejos comparado con un planeta y sus lunas, así que sus rayos se
+// toman paralelos dentro del mapa
+pub struct ShadowMap {
+    pub size: usize,
+    pub depth: Vec<f32>,
+    // Mundo -> espacio de clip de la luz, y de ahí a texels del mapa (x, y)
+    // con la profundidad en z, en [-1, 1]
+    pub light_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    // Radio de PCF: se promedian (2r+1)^2 comparaciones
+    pub pcf_radius: i32,
+    texel_world: f32,
+    depth_bias: f32,
+}
+
+impl ShadowMap {
+    pub fn new(size: usize) -> Self {
+        ShadowMap {
+            size,
+            depth: vec![f32::INFINITY; size * size],
+            light_matrix: Mat4::identity(),
+            viewport_matrix: create_viewport_matrix(size as f32, size as f32),
+            pcf_radius: 2,
+            texel_world: 0.0,
+            depth_bias: 0.0,
+        }
+    }
+
+    // Encuadra la esfera (`center`, `radius`) vista desde `light_dir` (hacia la
+    // luz) y vacía el mapa
+    pub fn fit(&mut self, center: Vec3, radius: f32, light_dir: Vec3) {
+        let up = if light_dir.y.abs() > 0.99 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let eye = center + light_dir.normalize() * (radius * 3.0);
+        let view = look_at(&eye, &center, &up);
+        let projection = ortho(-radius, radius, -radius, radius, radius, radius * 5.0);
+
+        self.light_matrix = projection * view;
+        self.texel_world = 2.0 * radius / self.size as f32;
+        // La profundidad recorre 4 radios en [-1, 1]
+        self.depth_bias = DEPTH_BIAS_TEXELS * self.texel_world * 2.0 / (radius * 4.0);
+        self.depth.fill(f32::INFINITY);
+    }
+
+    // Pasada de solo profundidad de un modelo ubicado con `model_matrix`
+    pub fn render_depth(&mut self, model: &Model, model_matrix: &Mat4) {
+        let light_model = self.light_matrix * model_matrix;
+        let vertices: Vec<Vertex> = model
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let mut transformed = vertex.clone();
+                transformed.clip_position = light_model * Vec4::new(vertex.position.x, vertex.position.y, vertex.position.z, 1.0);
+                transformed
+            })
+            .collect();
+
+        let mut triangles = Vec::new();
+        for triangle in model.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| &vertices[triangle[i] as usize]);
+            clip_triangle(corners, &self.viewport_matrix, &mut triangles);
+        }
+
+        for triangle in &triangles {
+            if let Some(rect) = triangle.bounding_box(self.size, self.size) {
+                triangle.rasterize(&rect, |sample| {
+                    let index = sample.y as usize * self.size + sample.x as usize;
+                    if sample.depth < self.depth[index] {
+                        self.depth[index] = sample.depth;
+                    }
+                });
+            }
+        }
+    }
+
+    // Fracción de la luz que llega a `point` (1 = iluminado, 0 = en sombra),
+    // promediando las comparaciones de los texels vecinos (PCF). La consulta
+    // se corre un poco sobre `normal` para no sombrearse a sí misma
+    pub fn visibility(&self, point: Vec3, normal: Vec3) -> f32 {
+        let offset = point + normal * (NORMAL_OFFSET_TEXELS * self.texel_world);
+        let projected = self.viewport_matrix * self.light_matrix * Vec4::new(offset.x, offset.y, offset.z, 1.0);
+        let x = projected.x.round() as i32;
+        let y = projected.y.round() as i32;
+        let depth = projected.z - self.depth_bias;
+
+        let mut lit = 0;
+        let mut total = 0;
+        for dy in -self.pcf_radius..=self.pcf_radius {
+            for dx in -self.pcf_radius..=self.pcf_radius {
+                total += 1;
+                if depth <= self.depth_at(x + dx, y + dy) {
+                    lit += 1;
+                }
+            }
+        }
+        lit as f32 / total as f32
+    }
+
+    // Fuera del mapa no hay nada que haga sombra
+    fn depth_at(&self, x: i32, y: i32) -> f32 {
+        if x < 0 || y < 0 || x >= self.size as i32 || y >= self.size as i32 {
+            f32::INFINITY
+        } else {
+            self.depth[y as usize * self.size + x as usize]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::create_uv_sphere;
+    use crate::shaders::create_model_matrix;
+
+    #[test]
+    fn occluders_shadow_what_is_behind_them() {
+        let mut map = ShadowMap::new(256);
+        let light_dir = Vec3::new(1.0, 0.0, 0.0);
+        map.fit(Vec3::zeros(), 3.0, light_dir);
+
+        // Esfera de radio 0.5 en x = 1, entre la luz (en +x) y el origen
+        let sphere = create_uv_sphere(1.0, 32, 16);
+        map.render_depth(&sphere, &create_model_matrix(Vec3::new(1.0, 0.0, 0.0), 0.5, Vec3::zeros()));
+
+        let facing_light = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(map.visibility(Vec3::new(-1.0, 0.0, 0.0), facing_light), 0.0);
+        assert_eq!(map.visibility(Vec3::new(-1.0, 1.5, 0.0), facing_light), 1.0);
+
+        // La cara iluminada de la propia esfera no se sombrea a sí misma
+        assert_eq!(map.visibility(Vec3::new(1.5, 0.0, 0.0), facing_light), 1.0);
+
+        // En el borde de la sombra el PCF da valores intermedios
+        let edge = map.visibility(Vec3::new(-1.0, 0.5, 0.0), facing_light);
+        assert!(edge > 0.0 && edge < 1.0, "borde = {}", edge);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::color::{linear_to_srgb, Color, HdrColor, Rgba};
+use crate::fragment::Fragment;
+use crate::shaders::{shade_surface, surface_diffuse, Uniforms};
+use crate::shader_registry::FragmentShader;
+
+use nalgebra_glm::Vec2;
+
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: WrapMode,
+    pub wrap_v: WrapMode,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "tamaño de textura inconsistente");
+        Texture { width, height, pixels }
+    }
+
+    // Carga PNG o JPEG como textura RGB
+    pub fn load_from_file(path: &str) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|e| format!("Error cargando textura {}: {}", path, e))?
+            .to_rgb8();
+
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Err(format!("La textura {} está vacía", path));
+        }
+
+        let pixels = image
+            .pixels()
+            .map(|p| Color::new(p[0], p[1], p[2]))
+            .collect();
+
+        Ok(Texture::new(width as usize, height as usize, pixels))
+    }
+
+    pub fn texel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler {
+            filter: Filter::Bilinear,
+            wrap_u: WrapMode::Repeat,
+            wrap_v: WrapMode::ClampToEdge,
+        }
+    }
+}
+
+impl Sampler {
+    pub fn new(filter: Filter, wrap: WrapMode) -> Self {
+        Sampler { filter, wrap_u: wrap, wrap_v: wrap }
+    }
+
+    // `uv` en convención OBJ: v = 0 es la fila inferior de la imagen
+    pub fn sample(&self, texture: &Texture, uv: Vec2) -> Color {
+        let x = uv.x * texture.width as f32;
+        let y = (1.0 - uv.y) * texture.height as f32;
+
+        match self.filter {
+            Filter::Nearest => {
+                let tx = wrap_index(x.floor() as i64, texture.width, self.wrap_u);
+                let ty = wrap_index(y.floor() as i64, texture.height, self.wrap_v);
+                texture.texel(tx, ty)
+            }
+            Filter::Bilinear => {
+                // Centros de texel en coordenadas enteras + 0.5
+                let fx = x - 0.5;
+                let fy = y - 0.5;
+                let x0 = fx.floor();
+                let y0 = fy.floor();
+                let tx = fx - x0;
+                let ty = fy - y0;
+
+                let x0i = wrap_index(x0 as i64, texture.width, self.wrap_u);
+                let x1i = wrap_index(x0 as i64 + 1, texture.width, self.wrap_u);
+                let y0i = wrap_index(y0 as i64, texture.height, self.wrap_v);
+                let y1i = wrap_index(y0 as i64 + 1, texture.height, self.wrap_v);
+
+                // Se mezcla en lineal: promediar los valores sRGB oscurece las transiciones
+                let texel = |x, y| HdrColor::from(texture.texel(x, y));
+                let top = texel(x0i, y0i).lerp(&texel(x1i, y0i), tx);
+                let bottom = texel(x0i, y1i).lerp(&texel(x1i, y1i), tx);
+                let encode = |value: f32| (linear_to_srgb(value) * 255.0).round() as u8;
+                let mixed = top.lerp(&bottom, ty);
+                Color::new(encode(mixed.r), encode(mixed.g), encode(mixed.b))
+            }
+        }
+    }
+}
+
+fn wrap_index(i: i64, size: usize, mode: WrapMode) -> usize {
+    let n = size as i64;
+    let wrapped = match mode {
+        WrapMode::Repeat => i.rem_euclid(n),
+        WrapMode::MirroredRepeat => {
+            let period = i.rem_euclid(2 * n);
+            if period < n { period } else { 2 * n - 1 - period }
+        }
+        WrapMode::ClampToEdge => i.clamp(0, n - 1),
+    };
+    wrapped as usize
+}
+
+// ============================================
+// SHADER: TEXTURA (albedo de imagen + iluminación difusa)
+// ============================================
+pub struct TexturedShader {
+    pub texture: Texture,
+    pub sampler: Sampler,
+}
+
+impl TexturedShader {
+    pub fn new(texture: Texture, sampler: Sampler) -> Self {
+        TexturedShader { texture, sampler }
+    }
+}
+
+impl FragmentShader for TexturedShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Rgba {
+        let albedo = self.sampler.sample(&self.texture, fragment.tex_coords);
+
+        let normal = fragment.normal.normalize();
+        let diffuse = surface_diffuse(fragment, uniforms, normal);
+
+        Rgba::opaque(shade_surface(albedo, 0.2, 0.8, diffuse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2x2: fila superior roja/verde, fila inferior azul/blanca
+    fn checker() -> Texture {
+        Texture::new(2, 2, vec![
+            Color::new(255, 0, 0), Color::new(0, 255, 0),
+            Color::new(0, 0, 255), Color::new(255, 255, 255),
+        ])
+    }
+
+    #[test]
+    fn nearest_uses_obj_orientation() {
+        let sampler = Sampler::new(Filter::Nearest, WrapMode::Repeat);
+        let texture = checker();
+
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.25, 0.75)).to_hex(), 0xFF0000);
+        assert_eq!(sampler.sample(&texture, Vec2::new(0.75, 0.25)).to_hex(), 0xFFFFFF);
+    }
+
+    #[test]
+    fn wrap_modes() {
+        assert_eq!(wrap_index(-1, 4, WrapMode::Repeat), 3);
+        assert_eq!(wrap_index(5, 4, WrapMode::Repeat), 1);
+        assert_eq!(wrap_index(-1, 4, WrapMode::MirroredRepeat), 0);
+        assert_eq!(wrap_index(5, 4, WrapMode::MirroredRepeat), 2);
+        assert_eq!(wrap_index(-3, 4, WrapMode::ClampToEdge), 0);
+        assert_eq!(wrap_index(9, 4, WrapMode::ClampToEdge), 3);
+    }
+
+    #[test]
+    fn bilinear_blends_between_texel_centers() {
+        let sampler = Sampler::new(Filter::Bilinear, WrapMode::ClampToEdge);
+        let texture = checker();
+
+        // Punto medio entre el texel rojo y el verde de la fila superior: mitad
+        // de la luz de cada uno, que en sRGB queda por encima de 127
+        let color = sampler.sample(&texture, Vec2::new(0.5, 0.75));
+        assert_eq!((color.r, color.g, color.b), (188, 188, 0));
+
+        // Un texel se devuelve tal cual en su centro
+        let color = sampler.sample(&texture, Vec2::new(0.25, 0.25));
+        assert_eq!(color.to_hex(), 0x0000FF);
+    }
+}
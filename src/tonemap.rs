@@ -0,0 +1,147 @@
+use crate::color::{linear_to_srgb, Color, HdrColor};
+
+// ============================================
+// TONE MAPPING: HDR lineal -> sRGB de 8 bits
+// ============================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneOperator {
+    // Recorta en 1.0: lo que había antes del buffer HDR
+    None,
+    // x / (1 + x): comprime las luces sin llegar nunca al blanco
+    Reinhard,
+    // Curva filmica ACES (ajuste de Narkowicz): más contraste en medios tonos
+    Aces,
+    // 1 - e^(-x): satura suave, como la película ante más luz
+    Exposure,
+}
+
+impl ToneOperator {
+    pub const ALL: [ToneOperator; 4] = [
+        ToneOperator::None,
+        ToneOperator::Reinhard,
+        ToneOperator::Aces,
+        ToneOperator::Exposure,
+    ];
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(ToneOperator::None),
+            "reinhard" => Ok(ToneOperator::Reinhard),
+            "aces" => Ok(ToneOperator::Aces),
+            "exposure" => Ok(ToneOperator::Exposure),
+            _ => Err(format!(
+                "Operador de tone mapping desconocido '{}' (disponibles: none, reinhard, aces, exposure)",
+                value
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneOperator::None => "none",
+            ToneOperator::Reinhard => "reinhard",
+            ToneOperator::Aces => "aces",
+            ToneOperator::Exposure => "exposure",
+        }
+    }
+
+    // El siguiente operador, para alternarlos desde el teclado
+    pub fn next(&self) -> Self {
+        let index = ToneOperator::ALL.iter().position(|op| op == self).unwrap_or(0);
+        ToneOperator::ALL[(index + 1) % ToneOperator::ALL.len()]
+    }
+
+    fn apply(&self, x: f32) -> f32 {
+        match self {
+            ToneOperator::None => x,
+            ToneOperator::Reinhard => x / (1.0 + x),
+            ToneOperator::Aces => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+            ToneOperator::Exposure => 1.0 - (-x).exp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneOperator,
+    // Multiplica la radiancia antes del operador
+    pub exposure: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping { operator: ToneOperator::Aces, exposure: 1.0 }
+    }
+}
+
+impl ToneMapping {
+    // Exposición, operador y codificación sRGB; los negativos y NaN quedan en negro
+    pub fn map(&self, color: HdrColor) -> Color {
+        encode_srgb(self.map_linear(color))
+    }
+
+    // Exposición y operador, sin codificar: valores lineales de pantalla en [0, 1]
+    pub fn map_linear(&self, color: HdrColor) -> HdrColor {
+        color.map(|value| {
+            let exposed = (value * self.exposure).max(0.0);
+            if exposed.is_nan() {
+                0.0
+            } else {
+                self.operator.apply(exposed).clamp(0.0, 1.0)
+            }
+        })
+    }
+}
+
+// Lineal de pantalla en [0, 1] -> sRGB de 8 bits
+pub fn encode_srgb(color: HdrColor) -> Color {
+    let encode = |value: f32| (linear_to_srgb(value.clamp(0.0, 1.0)) * 255.0).round() as u8;
+    Color::new(encode(color.r), encode(color.g), encode(color.b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn none_round_trips_8_bit_colors() {
+        let tone_mapping = ToneMapping { operator: ToneOperator::None, exposure: 1.0 };
+        for value in 0..=255u8 {
+            let color = tone_mapping.map(HdrColor::from(Color::new(value, value, 255 - value)));
+            assert_eq!((color.r, color.b), (value, 255 - value));
+        }
+    }
+
+    #[test]
+    fn operators_keep_highlights_apart() {
+        for operator in [ToneOperator::Reinhard, ToneOperator::Aces, ToneOperator::Exposure] {
+            let tone_mapping = ToneMapping { operator, exposure: 1.0 };
+            let bright = tone_mapping.map(HdrColor::new(2.0, 2.0, 2.0));
+            let brighter = tone_mapping.map(HdrColor::new(4.0, 4.0, 4.0));
+
+            // Sin recorte las dos luces siguen distinguiéndose
+            assert!(bright.r < brighter.r, "{:?} aplana las luces", operator);
+            assert_eq!(tone_mapping.map(HdrColor::black()).r, 0);
+        }
+
+        let clamp = ToneMapping { operator: ToneOperator::None, exposure: 1.0 };
+        assert_eq!(clamp.map(HdrColor::new(2.0, 2.0, 2.0)).r, clamp.map(HdrColor::new(4.0, 4.0, 4.0)).r);
+    }
+
+    #[test]
+    fn invalid_values_map_to_black() {
+        let color = ToneMapping::default().map(HdrColor::new(f32::NAN, -1.0, 0.0));
+        assert_eq!((color.r, color.g, color.b), (0, 0, 0));
+    }
+
+    #[test]
+    fn cycling_visits_every_operator() {
+        let mut operator = ToneOperator::None;
+        for _ in 0..ToneOperator::ALL.len() {
+            operator = operator.next();
+            assert_eq!(ToneOperator::parse(operator.name()), Ok(operator));
+        }
+        assert_eq!(operator, ToneOperator::None);
+    }
+}
@@ -1,8 +1,55 @@
-use crate::framebuffer::Framebuffer;
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
-use nalgebra_glm::Vec3;
+
+// Bits de subpíxel de las coordenadas de pantalla en punto fijo: los vértices
+// no se redondean al píxel y cada píxel se muestrea en su centro
+const SUBPIXEL_BITS: u32 = 4;
+const SUBPIXEL_SCALE: f32 = (1 << SUBPIXEL_BITS) as f32;
+const HALF_PIXEL: i64 = 1 << (SUBPIXEL_BITS - 1);
+
+// Rectángulo de píxeles con límites inclusivos
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelRect {
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl PixelRect {
+    pub fn intersect(&self, other: &PixelRect) -> Option<PixelRect> {
+        let rect = PixelRect {
+            min_x: self.min_x.max(other.min_x),
+            max_x: self.max_x.min(other.max_x),
+            min_y: self.min_y.max(other.min_y),
+            max_y: self.max_y.min(other.max_y),
+        };
+
+        if rect.min_x > rect.max_x || rect.min_y > rect.max_y {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+}
+
+// Orden de los vértices, vistos desde la cámara, que define la cara frontal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+// Píxel cubierto por un triángulo, con su profundidad y sus baricéntricas de
+// pantalla; todavía sin atributos interpolados
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub x: i32,
+    pub y: i32,
+    pub depth: f32,
+    weights: (f32, f32, f32),
+}
 
 pub struct Triangle {
     pub v1: Vertex,
@@ -15,62 +62,147 @@ impl Triangle {
         Triangle { v1, v2, v3 }
     }
 
-    pub fn draw(&self, framebuffer: &mut Framebuffer) -> Vec<Fragment> {
-        let mut fragments = Vec::new();
+    // Caja envolvente en píxeles (inclusiva) recortada a la pantalla
+    pub fn bounding_box(&self, width: usize, height: usize) -> Option<PixelRect> {
+        let (x1, y1) = (self.v1.transformed_position.x as i32, self.v1.transformed_position.y as i32);
+        let (x2, y2) = (self.v2.transformed_position.x as i32, self.v2.transformed_position.y as i32);
+        let (x3, y3) = (self.v3.transformed_position.x as i32, self.v3.transformed_position.y as i32);
+
+        let rect = PixelRect {
+            min_x: x1.min(x2).min(x3).max(0),
+            max_x: x1.max(x2).max(x3).min(width as i32 - 1),
+            min_y: y1.min(y2).min(y3).max(0),
+            max_y: y1.max(y2).max(y3).min(height as i32 - 1),
+        };
+
+        if rect.min_x > rect.max_x || rect.min_y > rect.max_y {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+
+    // Área con signo en pantalla (componente z de la normal geométrica). La
+    // pantalla tiene y hacia abajo, así que un triángulo antihorario en NDC
+    // queda con área negativa
+    pub fn signed_area(&self) -> f32 {
+        let edge1 = self.v2.transformed_position - self.v1.transformed_position;
+        let edge2 = self.v3.transformed_position - self.v1.transformed_position;
+        edge1.x * edge2.y - edge1.y * edge2.x
+    }
+
+    pub fn is_front_facing(&self, front_face: Winding) -> bool {
+        match front_face {
+            Winding::CounterClockwise => self.signed_area() < 0.0,
+            Winding::Clockwise => self.signed_area() > 0.0,
+        }
+    }
 
+    // Recorre los píxeles de `rect` (ya recortado a la pantalla) cubiertos por
+    // el triángulo. Solo calcula la profundidad: los demás atributos se
+    // interpolan con `fragment_at` si la muestra pasa el test de profundidad
+    pub fn rasterize<F: FnMut(Sample)>(&self, rect: &PixelRect, mut visit: F) {
         let v1_pos = self.v1.transformed_position;
         let v2_pos = self.v2.transformed_position;
         let v3_pos = self.v3.transformed_position;
 
-        let (x1, y1, z1) = (v1_pos.x as i32, v1_pos.y as i32, v1_pos.z);
-        let (x2, y2, z2) = (v2_pos.x as i32, v2_pos.y as i32, v2_pos.z);
-        let (x3, y3, z3) = (v3_pos.x as i32, v3_pos.y as i32, v3_pos.z);
-
-        let min_x = x1.min(x2).min(x3).max(0);
-        let max_x = x1.max(x2).max(x3).min(framebuffer.width as i32 - 1);
-        let min_y = y1.min(y2).min(y3).max(0);
-        let max_y = y1.max(y2).max(y3).min(framebuffer.height as i32 - 1);
-
-        let edge1 = v2_pos - v1_pos;
-        let edge2 = v3_pos - v1_pos;
-        let triangle_normal = nalgebra_glm::cross(&edge1, &edge2).normalize();
-
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let (w1, w2, w3) = barycentric_coordinates(
-                    x as f32, y as f32,
-                    x1 as f32, y1 as f32,
-                    x2 as f32, y2 as f32,
-                    x3 as f32, y3 as f32,
-                );
-
-                if w1 >= 0.0 && w2 >= 0.0 && w3 >= 0.0 {
+        let fixed = |value: f32| (value * SUBPIXEL_SCALE).round() as i64;
+        let (x1, y1, z1) = (fixed(v1_pos.x), fixed(v1_pos.y), v1_pos.z);
+        let (x2, y2, z2) = (fixed(v2_pos.x), fixed(v2_pos.y), v2_pos.z);
+        let (x3, y3, z3) = (fixed(v3_pos.x), fixed(v3_pos.y), v3_pos.z);
+
+        // Baricéntricas sin normalizar como funciones de arista en enteros, con
+        // el signo del área para que adentro sean positivas: son exactas, así
+        // que una muestra sobre una arista compartida la toma un solo triángulo
+        let area = (y2 - y3) * (x1 - x3) + (x3 - x2) * (y1 - y3);
+        if area == 0 {
+            return;
+        }
+        let sign = area.signum();
+        let gradient1 = ((y2 - y3) * sign, (x3 - x2) * sign);
+        let gradient2 = ((y3 - y1) * sign, (x1 - x3) * sign);
+        let gradient3 = (-gradient1.0 - gradient2.0, -gradient1.1 - gradient2.1);
+        let inverse_area = 1.0 / area.abs() as f32;
+
+        for y in rect.min_y..=rect.max_y {
+            for x in rect.min_x..=rect.max_x {
+                let px = ((x as i64) << SUBPIXEL_BITS) + HALF_PIXEL - x3;
+                let py = ((y as i64) << SUBPIXEL_BITS) + HALF_PIXEL - y3;
+                let e1 = gradient1.0 * px + gradient1.1 * py;
+                let e2 = gradient2.0 * px + gradient2.1 * py;
+                let e3 = area.abs() - e1 - e2;
+
+                if owns_edge(e1, gradient1) && owns_edge(e2, gradient2) && owns_edge(e3, gradient3) {
+                    let (w1, w2, w3) = (e1 as f32 * inverse_area, e2 as f32 * inverse_area, e3 as f32 * inverse_area);
+                    // La profundidad en pantalla es lineal; el resto de atributos no
                     let depth = z1 * w1 + z2 * w2 + z3 * w3;
-                    
-                    let normal = (self.v1.transformed_normal * w1 + 
-                                 self.v2.transformed_normal * w2 + 
-                                 self.v3.transformed_normal * w3).normalize();
-
-                    let intensity = 1.0;
-
-                    let fragment = Fragment::new_with_normal(
-                        x as f32,
-                        y as f32,
-                        framebuffer.current_color,
-                        depth,
-                        normal,
-                        intensity,
-                    );
-
-                    fragments.push(fragment);
+                    visit(Sample { x, y, depth, weights: (w1, w2, w3) });
                 }
             }
         }
+    }
+
+    // Interpola normal, tangente y coordenadas de textura para una muestra de `rasterize`
+    pub fn fragment_at(&self, sample: &Sample, color: Color) -> Fragment {
+        let (p1, p2, p3) = perspective_correct_weights(
+            sample.weights,
+            (self.v1.inv_w, self.v2.inv_w, self.v3.inv_w),
+        );
+
+        let normal = (self.v1.transformed_normal * p1 +
+                     self.v2.transformed_normal * p2 +
+                     self.v3.transformed_normal * p3).normalize();
+
+        let tex_coords = self.v1.tex_coords * p1 +
+                         self.v2.tex_coords * p2 +
+                         self.v3.tex_coords * p3;
+
+        let tangent = self.v1.transformed_tangent * p1 +
+                      self.v2.transformed_tangent * p2 +
+                      self.v3.transformed_tangent * p3;
+
+        let world_position = self.v1.world_position * p1 +
+                             self.v2.world_position * p2 +
+                             self.v3.world_position * p3;
+
+        let mut fragment = Fragment::new(color, normal);
+        fragment.tex_coords = tex_coords;
+        fragment.tangent = tangent;
+        fragment.world_position = world_position;
+        fragment
+    }
+}
+
+// Convierte baricéntricas de pantalla en pesos para atributos de espacio 3D,
+// dividiendo cada una por el w de clip de su vértice y renormalizando
+pub fn perspective_correct_weights(
+    (w1, w2, w3): (f32, f32, f32),
+    (inv_w1, inv_w2, inv_w3): (f32, f32, f32),
+) -> (f32, f32, f32) {
+    let p1 = w1 * inv_w1;
+    let p2 = w2 * inv_w2;
+    let p3 = w3 * inv_w3;
+    let sum = p1 + p2 + p3;
 
-        fragments
+    if sum.abs() < 1e-10 {
+        return (w1, w2, w3);
     }
+
+    (p1 / sum, p2 / sum, p3 / sum)
+}
+
+// Regla top-left: una muestra justo sobre una arista solo cuenta si es una
+// arista izquierda (el interior queda a la derecha) o una horizontal superior
+// (el interior queda abajo). De dos triángulos que comparten la arista la toma
+// exactamente uno, así las capas translúcidas no se mezclan dos veces en ella
+fn owns_edge(edge: i64, (gradient_x, gradient_y): (i64, i64)) -> bool {
+    edge > 0 || (edge == 0 && (gradient_x > 0 || (gradient_x == 0 && gradient_y > 0)))
 }
 
+// Baricéntricas en punto flotante; las pruebas la usan como referencia de la
+// interpolación afín en pantalla
+#[cfg(test)]
+#[allow(clippy::too_many_arguments)]
 fn barycentric_coordinates(
     px: f32, py: f32,
     ax: f32, ay: f32,
@@ -88,4 +220,150 @@ fn barycentric_coordinates(
     let w3 = 1.0 - w1 - w2;
 
     (w1, w2, w3)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipping::project;
+    use crate::shaders::{vertex_shader, create_viewport_matrix, Uniforms};
+    use nalgebra_glm::{look_at, perspective, Mat4, Vec2, Vec3};
+
+    const WIDTH: f32 = 400.0;
+    const HEIGHT: f32 = 400.0;
+    const FOV: f32 = std::f32::consts::PI / 3.0;
+
+    fn uniforms() -> Uniforms {
+        Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix: look_at(
+                &Vec3::new(0.0, 0.0, 0.0),
+                &Vec3::new(0.0, 0.0, -1.0),
+                &Vec3::new(0.0, 1.0, 0.0),
+            ),
+            projection_matrix: perspective(WIDTH / HEIGHT, FOV, 0.1, 100.0),
+            viewport_matrix: create_viewport_matrix(WIDTH, HEIGHT),
+            time: 0.0,
+            lights: crate::light::Lights::default(),
+            camera_position: Vec3::new(0.0, 0.0, 0.0),
+            occluders: crate::occlusion::Occluders::default(),
+            body_radius: 1.0,
+        }
+    }
+
+    // Intersección del rayo que pasa por el píxel (x, y) con el plano del triángulo
+    fn ray_plane_hit(x: f32, y: f32, points: &[Vec3; 3]) -> Vec3 {
+        let tan_half = (FOV / 2.0).tan();
+        let ndc_x = (x - WIDTH / 2.0) / (WIDTH / 2.0);
+        let ndc_y = -(y - HEIGHT / 2.0) / (HEIGHT / 2.0);
+        let dir = Vec3::new(ndc_x * tan_half * WIDTH / HEIGHT, ndc_y * tan_half, -1.0);
+
+        let plane_normal = (points[1] - points[0]).cross(&(points[2] - points[0]));
+        let t = plane_normal.dot(&points[0]) / plane_normal.dot(&dir);
+        dir * t
+    }
+
+    // Baricéntricas exactas de un punto sobre el plano del triángulo
+    fn world_barycentric(p: &Vec3, points: &[Vec3; 3]) -> (f32, f32, f32) {
+        let n = (points[1] - points[0]).cross(&(points[2] - points[0]));
+        let area = n.dot(&n);
+        let b1 = (points[2] - points[1]).cross(&(p - points[1])).dot(&n) / area;
+        let b2 = (points[0] - points[2]).cross(&(p - points[2])).dot(&n) / area;
+        (b1, b2, 1.0 - b1 - b2)
+    }
+
+    #[test]
+    fn normals_match_analytic_plane_under_perspective() {
+        // Plano inclinado que se aleja de la cámara: de z = -1.5 a z = -12
+        let points = [
+            Vec3::new(-1.0, -0.8, -1.5),
+            Vec3::new(1.0, -0.8, -1.5),
+            Vec3::new(0.0, 6.0, -12.0),
+        ];
+        let normals = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+
+        let uniforms = uniforms();
+        let vertices: Vec<Vertex> = points
+            .iter()
+            .zip(normals.iter())
+            .map(|(p, n)| project(&vertex_shader(&Vertex::new(*p, *n, Vec2::zeros()), &uniforms), &uniforms.viewport_matrix))
+            .collect();
+
+        let triangle = Triangle::new_from_vertices(vertices[0].clone(), vertices[1].clone(), vertices[2].clone());
+        let rect = triangle.bounding_box(WIDTH as usize, HEIGHT as usize).unwrap();
+        let mut samples = Vec::new();
+        triangle.rasterize(&rect, |sample| samples.push(sample));
+        assert!(samples.len() > 1000, "el triángulo debería cubrir muchos píxeles");
+
+        let mut max_error: f32 = 0.0;
+        let mut max_affine_error: f32 = 0.0;
+
+        for sample in &samples {
+            let fragment = triangle.fragment_at(sample, Color::black());
+
+            // Las muestras se toman en el centro del píxel
+            let (center_x, center_y) = (sample.x as f32 + 0.5, sample.y as f32 + 0.5);
+            let hit = ray_plane_hit(center_x, center_y, &points);
+            let (b1, b2, b3) = world_barycentric(&hit, &points);
+
+            // Ignorar el borde, donde domina el redondeo de los vértices al subpíxel
+            if b1.min(b2).min(b3) < 0.05 {
+                continue;
+            }
+
+            let expected = (normals[0] * b1 + normals[1] * b2 + normals[2] * b3).normalize();
+            max_error = max_error.max((fragment.normal - expected).magnitude());
+
+            // Referencia: lo que daría la interpolación afín en pantalla
+            let (a1, a2, a3) = barycentric_coordinates(
+                center_x, center_y,
+                vertices[0].transformed_position.x, vertices[0].transformed_position.y,
+                vertices[1].transformed_position.x, vertices[1].transformed_position.y,
+                vertices[2].transformed_position.x, vertices[2].transformed_position.y,
+            );
+            let affine = (normals[0] * a1 + normals[1] * a2 + normals[2] * a3).normalize();
+            max_affine_error = max_affine_error.max((affine - expected).magnitude());
+        }
+
+        assert!(max_error < 0.005, "error perspectiva-correcta demasiado grande: {}", max_error);
+        assert!(max_affine_error > 0.1, "la escena de prueba no distingue interpolación afín: {}", max_affine_error);
+    }
+
+    #[test]
+    fn front_faces_point_at_the_camera() {
+        // Esfera frente a la cámara: las caras que la miran quedan antihorarias
+        // en pantalla y las de atrás horarias
+        let uniforms = Uniforms { model_matrix: Mat4::new_translation(&Vec3::new(0.0, 0.0, -4.0)), ..uniforms() };
+        let sphere = crate::primitives::create_uv_sphere(1.0, 32, 16);
+        let (mut front, mut back) = (0, 0);
+
+        for corners in sphere.triangles() {
+            let world = corners.map(|v| (uniforms.model_matrix * v.position.push(1.0)).xyz());
+            let normal = (world[1] - world[0]).cross(&(world[2] - world[0])).normalize();
+            let facing = normal.dot(&(uniforms.camera_position - world[0]).normalize());
+            // Las caras casi de canto dependen del redondeo al subpíxel
+            if facing.abs() < 0.05 {
+                continue;
+            }
+
+            let [a, b, c] = corners.map(|v| project(&vertex_shader(v, &uniforms), &uniforms.viewport_matrix));
+            let triangle = Triangle::new_from_vertices(a, b, c);
+            assert_eq!(triangle.is_front_facing(Winding::CounterClockwise), facing > 0.0);
+            assert_eq!(triangle.is_front_facing(Winding::Clockwise), facing < 0.0);
+            if facing > 0.0 { front += 1 } else { back += 1 }
+        }
+        assert!(front > 100 && back > 100);
+    }
+
+    #[test]
+    fn equal_depths_keep_screen_weights() {
+        let weights = perspective_correct_weights((0.2, 0.3, 0.5), (0.25, 0.25, 0.25));
+        assert!((weights.0 - 0.2).abs() < 1e-6);
+        assert!((weights.1 - 0.3).abs() < 1e-6);
+        assert!((weights.2 - 0.5).abs() < 1e-6);
+    }
+}
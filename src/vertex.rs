@@ -1,4 +1,4 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
 #[derive(Clone, Debug)]
@@ -6,9 +6,19 @@ pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub tex_coords: Vec2,
+    // Dirección de +u sobre la superficie; w = +-1 es el signo de la bitangente
+    pub tangent: Vec4,
     pub color: Color,
+    // Salida del vertex shader, antes del recorte y la división por w
+    pub clip_position: Vec4,
+    // Posición en pantalla (x, y en píxeles, z de profundidad) tras el recorte
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub transformed_tangent: Vec4,
+    // Posición en espacio de mundo, para sombras e iluminación por fragmento
+    pub world_position: Vec3,
+    // 1/w de espacio de clip, para interpolar con corrección de perspectiva
+    pub inv_w: f32,
 }
 
 impl Vertex {
@@ -17,27 +27,16 @@ impl Vertex {
             position,
             normal,
             tex_coords,
+            tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
             color: Color::black(),
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
             transformed_position: position,
             transformed_normal: normal,
+            transformed_tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            world_position: position,
+            inv_w: 1.0,
         }
     }
-
-    pub fn new_with_color(position: Vec3, color: Color) -> Self {
-        Vertex {
-            position,
-            normal: Vec3::new(0.0, 0.0, 0.0),
-            tex_coords: Vec2::new(0.0, 0.0),
-            color,
-            transformed_position: Vec3::new(0.0, 0.0, 0.0),
-            transformed_normal: Vec3::new(0.0, 0.0, 0.0),
-        }
-    }
-
-    pub fn set_transformed(&mut self, position: Vec3, normal: Vec3) {
-        self.transformed_position = position;
-        self.transformed_normal = normal;
-    }
 }
 
 impl Default for Vertex {
@@ -46,9 +45,14 @@ impl Default for Vertex {
             position: Vec3::new(0.0, 0.0, 0.0),
             normal: Vec3::new(0.0, 1.0, 0.0),
             tex_coords: Vec2::new(0.0, 0.0),
+            tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
             color: Color::black(),
+            clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+            transformed_tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+            world_position: Vec3::new(0.0, 0.0, 0.0),
+            inv_w: 1.0,
         }
     }
 }
\ No newline at end of file